@@ -1,46 +1,395 @@
-use std::{borrow::Borrow, fmt, hash};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::HashSet,
+    convert::Infallible,
+    fmt, hash,
+    ops::{Deref, RangeBounds},
+    str::{FromStr, Utf8Error},
+    sync::{Arc, Mutex, OnceLock},
+};
 
+use crate::value::shared::{self, Shared};
 use bytes::Bytes;
-use serde::{
-    de::{Error, Visitor},
-    Deserialize, Deserializer, Serialize,
-};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Maximum length of the strings stored inline. `Repr` is as large as a
+/// `Bytes` and a tag, so this does not make it larger.
+const INLINE_CAPACITY: usize = 38;
+
+#[derive(Clone)]
+pub struct ByteString(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    /// Short string stored without allocation or reference counting.
+    Inline(Inline),
+    /// The second field is the length of the buffer the bytes are a slice of,
+    /// see `ByteString::stored`.
+    Str(Bytes, u32),
+    /// Content known not to contain any character escaped in JSON, so that it
+    /// can be copied as is into the output.
+    EscapeFree(Bytes, u32),
+    /// Content of a JSON string literal that still contains escape sequences,
+    /// decoded on first access.
+    Escaped(Arc<Escaped>),
+}
+
+#[derive(Clone, Copy)]
+struct Inline {
+    len: u8,
+    buf: [u8; INLINE_CAPACITY],
+}
+
+struct Escaped {
+    raw: Bytes,
+    buffer_len: u32,
+    unescaped: OnceLock<Bytes>,
+}
 
-#[derive(Clone, Eq, PartialEq, PartialOrd, Ord)]
-pub struct ByteString(Bytes);
+impl Inline {
+    fn new(s: &str) -> Option<Self> {
+        if s.len() > INLINE_CAPACITY {
+            return None;
+        }
+        let mut buf = [0; INLINE_CAPACITY];
+        buf[..s.len()].copy_from_slice(s.as_bytes());
+        Some(Inline {
+            len: s.len() as u8,
+            buf,
+        })
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len as usize]
+    }
+}
+
+impl Escaped {
+    fn unescaped(&self) -> &Bytes {
+        self.unescaped.get_or_init(|| {
+            let mut out = Vec::with_capacity(self.raw.len());
+            crate::parser::unescape(&self.raw, &mut out, false)
+                .expect("escape sequences are validated when parsing");
+            out.into()
+        })
+    }
+}
 
-/// read only string backed by a `Bytes` buffer
+/// read only string backed by a `Bytes` buffer, or stored inline if it is
+/// short
 impl ByteString {
+    /// Creates a string referencing `s` without copying it.
+    pub const fn from_static(s: &'static str) -> Self {
+        ByteString(Repr::Str(Bytes::from_static(s.as_bytes()), 0))
+    }
+
+    /// Stores `s` inline if it is short enough.
+    pub(crate) fn inline(s: &str) -> Option<Self> {
+        Inline::new(s).map(|inline| ByteString(Repr::Inline(inline)))
+    }
+
     /// will panic if `string` is not contained in `origin`
     pub(crate) fn new(origin: &Bytes, string: &str) -> Self {
-        ByteString(origin.slice_ref(string.as_bytes()))
+        ByteString(Repr::Str(
+            origin.slice_ref(string.as_bytes()),
+            buffer_len(origin.len()),
+        ))
+    }
+
+    /// `string` must be the content of a JSON string literal without escape
+    /// sequences, which cannot contain quotes, backslashes or control
+    /// characters. Will panic if `string` is not contained in `origin`
+    pub(crate) fn new_escape_free(origin: &Bytes, string: &str) -> Self {
+        ByteString(Repr::EscapeFree(
+            origin.slice_ref(string.as_bytes()),
+            buffer_len(origin.len()),
+        ))
+    }
+
+    /// `bytes` must be valid UTF-8
+    pub(crate) fn from_validated(bytes: Bytes) -> Self {
+        let len = buffer_len(bytes.len());
+        ByteString(Repr::Str(bytes, len))
+    }
+
+    /// `bytes` must be valid UTF-8 and must not need escaping in JSON
+    pub(crate) fn escape_free(bytes: Bytes) -> Self {
+        let len = buffer_len(bytes.len());
+        ByteString(Repr::EscapeFree(bytes, len))
+    }
+
+    /// `raw` must be the content of a valid JSON string literal, between the
+    /// quotes.
+    pub(crate) fn escaped(raw: Bytes) -> Self {
+        ByteString(Repr::Escaped(Arc::new(Escaped {
+            buffer_len: buffer_len(raw.len()),
+            raw,
+            unescaped: OnceLock::new(),
+        })))
+    }
+
+    /// Records that the buffer of this string is a slice of a buffer of `len`
+    /// bytes, which it keeps alive.
+    pub(crate) fn within(mut self, len: usize) -> Self {
+        let len = buffer_len(len);
+        match &mut self.0 {
+            Repr::Inline(_) => {}
+            Repr::Str(_, buffer) | Repr::EscapeFree(_, buffer) => *buffer = len,
+            Repr::Escaped(escaped) => {
+                // only called on new strings, which are not shared yet
+                if let Some(escaped) = Arc::get_mut(escaped) {
+                    escaped.buffer_len = len;
+                }
+            }
+        }
+        self
     }
 
     pub fn as_str(&self) -> &str {
-        // `ByteString` can only be created from a valid `&str`
-        unsafe { std::str::from_utf8_unchecked(&self.0) }
+        // `ByteString` can only be created from a valid `&str`, or from a
+        // valid JSON string literal
+        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+    }
+
+    /// Returns the content of the string as `Bytes`. The buffer is shared,
+    /// except for short strings, which are stored inline and copied.
+    pub fn inner(&self) -> Bytes {
+        self.clone().into_bytes()
+    }
+
+    /// Returns the bytes of the string. A string parsed with escape sequences
+    /// left in place is decoded on the first call.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Inline(inline) => inline.as_bytes(),
+            Repr::Str(bytes, _) | Repr::EscapeFree(bytes, _) => bytes,
+            Repr::Escaped(escaped) => escaped.unescaped(),
+        }
     }
 
-    pub const fn inner(&self) -> &Bytes {
-        &self.0
+    /// Returns the substring in `range`, sharing the buffer of this string.
+    ///
+    /// Will panic if the range is out of bounds or does not fall on
+    /// character boundaries, like indexing a `str`.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        match self.as_str().get(bounds) {
+            Some(sub) => self.substring(sub),
+            None => panic!(
+                "range {bounds:?} out of bounds or not on a char boundary of {:?}",
+                self.as_str()
+            ),
+        }
     }
+
+    /// Returns the substrings separated by `delimiter`, sharing the buffer of
+    /// this string. `str::split`, reached through `Deref`, borrows them
+    /// instead.
+    pub fn split_bytes<'a>(&'a self, delimiter: &'a str) -> impl Iterator<Item = ByteString> + 'a {
+        self.as_str()
+            .split(delimiter)
+            .map(move |sub| self.substring(sub))
+    }
+
+    /// Returns the string without leading and trailing whitespace, sharing
+    /// the buffer of this string. `str::trim`, reached through `Deref`,
+    /// borrows it instead.
+    pub fn trim_bytes(&self) -> Self {
+        self.substring(self.as_str().trim())
+    }
+
+    /// `sub` must be a substring of `self.as_str()`.
+    fn substring(&self, sub: &str) -> Self {
+        match &self.0 {
+            Repr::Inline(_) => ByteString::inline(sub).expect("substrings are shorter"),
+            Repr::Str(bytes, len) => ByteString(Repr::Str(bytes.slice_ref(sub.as_bytes()), *len)),
+            Repr::EscapeFree(bytes, len) => {
+                ByteString(Repr::EscapeFree(bytes.slice_ref(sub.as_bytes()), *len))
+            }
+            Repr::Escaped(escaped) => {
+                let unescaped = escaped.unescaped();
+                ByteString::from_validated(unescaped.slice_ref(sub.as_bytes()))
+                    .within(unescaped.len())
+            }
+        }
+    }
+
+    /// Returns the content of the string as `Bytes`, copying it if it is
+    /// stored inline.
+    pub(crate) fn into_bytes(self) -> Bytes {
+        match self.0 {
+            Repr::Inline(inline) => Bytes::copy_from_slice(inline.as_bytes()),
+            Repr::Str(bytes, _) | Repr::EscapeFree(bytes, _) => bytes,
+            Repr::Escaped(escaped) => escaped.unescaped().clone(),
+        }
+    }
+
+    /// Returns the buffer this string references: its content, or its escaped
+    /// content if it was parsed with escape sequences left in place, along
+    /// with the length of the buffer it is a slice of. Strings stored inline
+    /// do not reference any.
+    ///
+    /// The length is the one of the input a string was parsed from, or of
+    /// the string itself if it was copied into its own buffer. It is 0 for
+    /// static strings.
+    pub(crate) fn stored(&self) -> Option<(&Bytes, usize)> {
+        match &self.0 {
+            Repr::Inline(_) => None,
+            Repr::Str(bytes, len) | Repr::EscapeFree(bytes, len) => Some((bytes, *len as usize)),
+            Repr::Escaped(escaped) => Some((&escaped.raw, escaped.buffer_len as usize)),
+        }
+    }
+
+    /// Returns a string with the same content, referencing `stored` instead.
+    pub(crate) fn with_stored(&self, stored: Bytes) -> Self {
+        match &self.0 {
+            Repr::Inline(_) | Repr::Str(..) => ByteString::from_validated(stored),
+            Repr::EscapeFree(..) => ByteString::escape_free(stored),
+            Repr::Escaped(_) => ByteString::escaped(stored),
+        }
+    }
+
+    /// Returns the escaped content of a string parsed with escape sequences
+    /// left in place, which can be written back to JSON as is.
+    pub(crate) fn raw_escaped(&self) -> Option<&Bytes> {
+        match &self.0 {
+            Repr::Inline(_) | Repr::Str(..) | Repr::EscapeFree(..) => None,
+            Repr::Escaped(escaped) => Some(&escaped.raw),
+        }
+    }
+
+    /// Returns the content of a string known not to need escaping, which can
+    /// be written to JSON between quotes without scanning it.
+    pub(crate) fn escape_free_bytes(&self) -> Option<&Bytes> {
+        match &self.0 {
+            Repr::EscapeFree(bytes, _) => Some(bytes),
+            Repr::Inline(_) | Repr::Str(..) | Repr::Escaped(_) => None,
+        }
+    }
+}
+
+/// Lengths of buffers are stored as `u32` to keep strings small. Larger
+/// buffers are counted as `u32::MAX` bytes.
+fn buffer_len(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+/// Returns whether `string` lies entirely inside `input`.
+pub(crate) fn contains(input: &Bytes, string: &str) -> bool {
+    let range = input.as_ptr_range();
+    let start = string.as_ptr();
+    range.start <= start && start.wrapping_add(string.len()) <= range.end
 }
 
 impl From<String> for ByteString {
     fn from(s: String) -> Self {
-        ByteString(s.into())
+        ByteString::inline(&s).unwrap_or_else(|| ByteString::from_validated(s.into()))
     }
 }
 
 impl From<&str> for ByteString {
     fn from(s: &str) -> Self {
-        ByteString(s.to_string().into())
+        ByteString::inline(s)
+            .unwrap_or_else(|| ByteString::from_validated(Bytes::copy_from_slice(s.as_bytes())))
+    }
+}
+
+impl TryFrom<Bytes> for ByteString {
+    type Error = Utf8Error;
+
+    /// Checks that `bytes` is valid UTF-8, and references it.
+    fn try_from(bytes: Bytes) -> Result<Self, Self::Error> {
+        std::str::from_utf8(&bytes)?;
+        Ok(ByteString::from_validated(bytes))
+    }
+}
+
+impl FromStr for ByteString {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl From<ByteString> for Bytes {
+    fn from(s: ByteString) -> Self {
+        s.into_bytes()
+    }
+}
+
+impl From<ByteString> for String {
+    fn from(s: ByteString) -> Self {
+        s.as_str().to_owned()
+    }
+}
+
+impl Default for ByteString {
+    fn default() -> Self {
+        ByteString::from("")
+    }
+}
+
+impl PartialEq for ByteString {
+    fn eq(&self, other: &ByteString) -> bool {
+        let (a, b) = (self.as_bytes(), other.as_bytes());
+        // interned strings share their buffer
+        a.len() == b.len() && (a.as_ptr() == b.as_ptr() || a == b)
+    }
+}
+
+impl Eq for ByteString {}
+
+impl PartialOrd for ByteString {
+    fn partial_cmp(&self, other: &ByteString) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByteString {
+    fn cmp(&self, other: &ByteString) -> Ordering {
+        let (a, b) = (self.as_bytes(), other.as_bytes());
+        if a.as_ptr() == b.as_ptr() && a.len() == b.len() {
+            return Ordering::Equal;
+        }
+        a.cmp(b)
     }
 }
 
 impl PartialEq<ByteString> for String {
     fn eq(&self, other: &ByteString) -> bool {
-        self.as_bytes() == other.0
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<String> for ByteString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<str> for ByteString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for ByteString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<ByteString> for str {
+    fn eq(&self, other: &ByteString) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<ByteString> for &str {
+    fn eq(&self, other: &ByteString) -> bool {
+        self.as_bytes() == other.as_bytes()
     }
 }
 
@@ -50,39 +399,144 @@ impl Borrow<str> for ByteString {
     }
 }
 
+impl Deref for ByteString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ByteString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for ByteString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
 impl std::fmt::Debug for ByteString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Debug::fmt(self.as_str(), f)
     }
 }
 
+impl fmt::Display for ByteString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
 impl hash::Hash for ByteString {
     fn hash<H: hash::Hasher>(&self, hasher: &mut H) {
         self.as_str().hash(hasher)
     }
 }
 
-struct ByteStringVisitor;
+/// A set of shared strings, returning the same [`ByteString`] for each
+/// occurrence of a key instead of a new copy.
+///
+/// Keys are copied once into their own buffer, so the interner does not keep
+/// the inputs they were parsed from alive. Equal keys longer than the inline
+/// capacity then share their buffer, and comparing them stops at the pointer.
+///
+/// The interner can be used for a single parse, with
+/// [`ParseOptions::key_interner`](crate::ParseOptions::key_interner) or
+/// [`BytesSeed::with_interner`](crate::value::BytesSeed::with_interner), or
+/// shared between threads for a known set of keys. It only grows, until it is
+/// cleared or reaches the limit set with [`KeyInterner::with_limit`].
+///
+/// [`Map::insert`](crate::Map::insert) does not intern keys by itself, since
+/// that would take a lock for every insertion: build the keys with
+/// [`KeyInterner::intern`] to share them.
+///
+/// ```
+/// # use serde_json_bytes::{json, KeyInterner, Map};
+/// let interner = KeyInterner::new();
+/// let mut objects = Vec::new();
+/// for id in 0..3 {
+///     let mut map = Map::new();
+///     map.insert(interner.intern("a_rather_long_key_name_used_by_every_object"), json!(id));
+///     objects.push(map);
+/// }
+///
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Default)]
+pub struct KeyInterner {
+    keys: Mutex<HashSet<ByteString, ahash::RandomState>>,
+    limit: Option<usize>,
+}
 
-impl<'de> Visitor<'de> for ByteStringVisitor {
-    type Value = ByteString;
+impl KeyInterner {
+    pub fn new() -> Self {
+        KeyInterner::default()
+    }
 
-    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("a string")
+    /// Creates an interner holding at most `limit` keys. Once it is full,
+    /// new keys are copied for each occurrence instead of being added, so
+    /// that an input with many distinct keys cannot grow it without bound.
+    ///
+    /// ```
+    /// # use serde_json_bytes::KeyInterner;
+    /// let interner = KeyInterner::with_limit(1);
+    /// interner.intern("a_rather_long_key_name_used_by_every_object");
+    /// interner.intern("another_long_key_name_that_is_not_interned");
+    ///
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn with_limit(limit: usize) -> Self {
+        KeyInterner {
+            keys: Mutex::default(),
+            limit: Some(limit),
+        }
     }
 
-    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(v.into())
+    /// Returns the shared string equal to `key`, adding it if needed.
+    pub fn intern(&self, key: &str) -> ByteString {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(string) = keys.get(key) {
+            return string.clone();
+        }
+        let string = ByteString::inline(key).unwrap_or_else(|| {
+            let bytes = Bytes::copy_from_slice(key.as_bytes());
+            if crate::value::encode::str_len(key) == key.len() + 2 {
+                ByteString::escape_free(bytes)
+            } else {
+                ByteString::from_validated(bytes)
+            }
+        });
+        if !matches!(self.limit, Some(limit) if keys.len() >= limit) {
+            keys.insert(string.clone());
+        }
+        string
     }
 
-    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
-    where
-        E: Error,
-    {
-        Ok(v.into())
+    /// Returns the number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all the keys. The strings already returned are still valid.
+    pub fn clear(&self) {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl fmt::Debug for KeyInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyInterner")
+            .field("len", &self.len())
+            .field("limit", &self.limit)
+            .finish()
     }
 }
 
@@ -91,16 +545,161 @@ impl<'de> Deserialize<'de> for ByteString {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_str(ByteStringVisitor)
+        deserializer.deserialize_str(shared::StringVisitor)
     }
 }
 
+/// Serializers of other crates get the string with `serialize_str`, and scan
+/// it for characters to escape even if it was parsed without escape
+/// sequences: serde has no call for text that is known not to need escaping.
+/// The serializer of [`to_value`](crate::to_value) takes the string as is,
+/// and [`Value::to_bytes`](crate::Value::to_bytes) copies it without scanning
+/// it.
 impl Serialize for ByteString {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: ::serde::Serializer,
     {
-        serializer.serialize_str(self.as_str())
+        if shared::takes_tokens::<S>() {
+            serializer.serialize_newtype_struct(shared::STRING_TOKEN, &Shared(self))
+        } else {
+            serializer.serialize_str(self.as_str())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteString, KeyInterner, Repr, INLINE_CAPACITY};
+    use bytes::Bytes;
+
+    #[test]
+    fn inline() {
+        let short = "s".repeat(INLINE_CAPACITY);
+        let long = "l".repeat(INLINE_CAPACITY + 1);
+        for (s, inline) in [("", true), ("id", true), (&short, true), (&long, false)] {
+            for string in [ByteString::from(s), ByteString::from(s.to_owned())] {
+                assert_eq!(matches!(string.0, Repr::Inline(_)), inline, "{s}");
+                assert_eq!(string.as_str(), s);
+                assert_eq!(string.clone(), string);
+                assert_eq!(string.into_bytes(), s);
+            }
+        }
+
+        // parsed strings keep referencing the input
+        let input = Bytes::from_static(b"id");
+        let parsed = ByteString::new(&input, "id");
+        assert!(matches!(parsed.0, Repr::Str(..)));
+        assert_eq!(parsed, ByteString::from("id"));
+        assert_eq!(parsed.as_str().as_ptr(), input.as_ptr());
+        assert_eq!(parsed.inner().as_ptr(), input.as_ptr());
+        assert_eq!(parsed.as_bytes(), b"id");
+
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(std::mem::size_of::<ByteString>(), 40);
+    }
+
+    #[test]
+    fn interner() {
+        let interner = KeyInterner::new();
+        let long = "k".repeat(INLINE_CAPACITY + 1);
+        let a = interner.intern(&long);
+        let b = interner.intern(&long);
+        assert_eq!(a.as_str().as_ptr(), b.as_str().as_ptr());
+        assert!(matches!(a.0, Repr::EscapeFree(..)));
+        assert!(matches!(interner.intern("id").0, Repr::Inline(_)));
+        let escaped = interner.intern(&format!("{long}\n"));
+        assert!(matches!(escaped.0, Repr::Str(..)));
+        assert_eq!(interner.len(), 3);
+
+        assert_eq!(a, b);
+        assert_eq!(a, ByteString::from(long.as_str()));
+        assert_ne!(a, escaped);
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Equal);
+        assert!(a < escaped);
+
+        let limited = KeyInterner::with_limit(1);
+        let first = limited.intern(&long);
+        let other = format!("{long}k");
+        let (c, d) = (limited.intern(&other), limited.intern(&other));
+        assert_eq!(c, d);
+        assert_ne!(c.as_str().as_ptr(), d.as_str().as_ptr());
+        assert_eq!(limited.len(), 1);
+        assert_eq!(
+            limited.intern(&long).as_str().as_ptr(),
+            first.as_str().as_ptr()
+        );
+        limited.clear();
+        limited.intern(&other);
+        assert_eq!(limited.len(), 1);
+    }
+
+    #[test]
+    fn serialize() {
+        let long = "s".repeat(INLINE_CAPACITY + 1);
+        let input = Bytes::from(format!(r#"["{long}"]"#));
+        let value = crate::Value::from_bytes(input.clone()).unwrap();
+        let strings: Vec<ByteString> = crate::from_value(value).unwrap();
+        assert!(matches!(strings[0].0, Repr::EscapeFree(..)));
+
+        match &crate::to_value(&strings).unwrap()[0] {
+            crate::Value::String(string) => assert!(matches!(string.0, Repr::EscapeFree(..))),
+            other => panic!("expected a string, got {other:?}"),
+        }
+        assert_eq!(serde_json::to_vec(&strings).unwrap(), input);
+    }
+
+    #[test]
+    fn string_traits() {
+        const STATIC: ByteString = ByteString::from_static("static");
+        assert_eq!(STATIC, "static");
+        assert_eq!("static", STATIC);
+        assert_eq!(*"static", STATIC);
+        assert_eq!(STATIC, "static".to_owned());
+        assert_eq!(STATIC.len(), 6);
+        assert!(STATIC.starts_with("st"));
+        assert_eq!(STATIC.to_string(), "static");
+        assert_eq!(format!("{STATIC:>8}"), "  static");
+        assert_eq!("static".parse::<ByteString>().unwrap(), STATIC);
+        assert_eq!(String::from(STATIC), "static");
+        assert_eq!(Bytes::from(STATIC), "static");
+        assert_eq!(AsRef::<[u8]>::as_ref(&STATIC), b"static");
+        assert_eq!(ByteString::default(), "");
+
+        assert_eq!(
+            ByteString::try_from(Bytes::from_static(b"ok")).unwrap(),
+            "ok"
+        );
+        assert!(ByteString::try_from(Bytes::from_static(b"\xFF")).is_err());
+    }
+
+    #[test]
+    fn substrings() {
+        let input = Bytes::from(format!(" {}, b ,c\t", "a".repeat(INLINE_CAPACITY)));
+        let string = ByteString::try_from(input.clone()).unwrap();
+        let in_input = |s: &ByteString| input.as_ptr_range().contains(&s.as_ptr());
+
+        let trimmed = string.trim_bytes();
+        assert_eq!(trimmed, string.as_str().trim());
+        assert!(in_input(&trimmed));
+        let parts: Vec<ByteString> = trimmed.split_bytes(",").collect();
+        assert_eq!(parts, [&"a".repeat(INLINE_CAPACITY), " b ", "c"]);
+        assert!(parts.iter().all(in_input));
+        assert_eq!(string.slice(1..3), "aa");
+        assert!(in_input(&string.slice(1..)));
+        assert_eq!(string.slice(..0), "");
+
+        let inline = ByteString::from(" é ");
+        assert_eq!(inline.trim_bytes(), "é");
+        assert_eq!(inline.slice(1..3), "é");
+        let escaped = ByteString::escaped(Bytes::from_static(br" a\n "));
+        assert_eq!(escaped.trim_bytes(), "a");
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_char_boundary() {
+        ByteString::from("é").slice(1..);
     }
 }
@@ -23,7 +23,7 @@ mod lib {
     pub use self::core::marker::{self, PhantomData};
     pub use self::core::ops::{Bound, RangeBounds};
     pub use self::core::result::{self, Result};
-    pub use self::core::{borrow, char, cmp, iter, mem, num, ops, slice, str};
+    pub use self::core::{any, borrow, char, cmp, iter, mem, num, ops, slice, str};
 
     #[cfg(not(feature = "std"))]
     pub use alloc::borrow::{Cow, ToOwned};
@@ -69,14 +69,20 @@ macro_rules! tri {
 }
 
 mod bytestring;
+mod macros;
 pub mod map;
 #[cfg(feature = "arbitrary_precision")]
 mod number;
+mod parser;
 pub mod value;
 
-pub use bytestring::ByteString;
+pub use bytestring::{ByteString, KeyInterner};
 pub use map::*;
-pub use value::{from_value, to_value, Value};
+pub use value::{
+    from_bytes, from_value, to_value, DuplicateKeys, Event, EventReader, JsonLines,
+    JsonLinesWriter, JsonSeq, JsonSeqWriter, JsonWriter, LimitExceeded, ParseOptions, Value,
+    ValueParser,
+};
 pub mod path;
 
 impl From<serde_json::Value> for Value {
@@ -94,14 +100,29 @@ impl From<serde_json::Value> for Value {
     }
 }
 
-pub use serde_json;
-
-#[macro_export]
-macro_rules! json {
-    ($($json:tt)+) => {
-        {
-            let value: $crate::Value = $crate::serde_json::json!($($json)+).into();
-            value
+impl From<Value> for serde_json::Value {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(b),
+            Value::Number(n) => serde_json::Value::Number(n),
+            Value::String(s) => serde_json::Value::String(s.into()),
+            Value::Array(v) => serde_json::Value::Array(v.into_iter().map(Into::into).collect()),
+            Value::Object(o) => serde_json::Value::Object(o.into()),
+            Value::Raw(raw) => {
+                // only created from valid JSON text, including when deserialized
+                serde_json::from_str(raw.get()).expect("raw values are validated JSON")
+            }
         }
-    };
+    }
 }
+
+impl From<Map<ByteString, Value>> for serde_json::Map<String, serde_json::Value> {
+    fn from(map: Map<ByteString, Value>) -> Self {
+        map.into_iter().map(|(k, v)| (k.into(), v.into())).collect()
+    }
+}
+
+#[doc(hidden)]
+pub use macros::__private;
+pub use serde_json;
@@ -0,0 +1,484 @@
+/// Constructs a [`Value`](crate::Value) from a JSON literal.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, ByteString, Value};
+/// let name = ByteString::from("Ferris");
+/// let friends = json!(["Crab", "Lobster"]);
+/// let raw = Bytes::from_static(b"snippet");
+///
+/// let value = json!({
+///     "name": name,
+///     "age": 7,
+///     "friends": friends,
+///     "tags": [raw, null, true],
+///     "nested": {"empty": {}},
+/// });
+///
+/// assert_eq!(value["friends"][1], "Lobster");
+/// assert_eq!(value["tags"][0], "snippet");
+/// ```
+///
+/// Unlike `serde_json::json!`, the value is built directly, without an
+/// intermediate `serde_json::Value`. Interpolated expressions that convert
+/// into a `Value`, like a `Value`, `Map`, `ByteString` or `String`, are moved
+/// in as they are. `Bytes` are used as a string without copying them, and
+/// must be valid UTF-8. Other expressions are serialized with
+/// [`to_value`](crate::to_value), and the macro panics if that fails.
+///
+/// Keys written as string literals are stored with
+/// [`ByteString::from_static`](crate::ByteString::from_static), and do not
+/// allocate. Other keys are any expression converting into a `ByteString` or
+/// a `String`, wrapped in parentheses or brackets if they contain several
+/// tokens.
+#[macro_export]
+macro_rules! json {
+    // Hide distracting implementation details from the generated rustdoc.
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+// Same rules as the `json!` macro of serde_json, building our `Value` instead.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    //////////////////////////////////////////////////////////////////////////
+    // TT muncher for parsing the inside of an array [...]. Produces a vec![...]
+    // of the elements.
+    //
+    // Must be invoked as: json_internal!(@array [] $($tt)*)
+    //////////////////////////////////////////////////////////////////////////
+
+    // Done with trailing comma.
+    (@array [$($elems:expr,)*]) => {
+        $crate::__private::vec![$($elems,)*]
+    };
+
+    // Done without trailing comma.
+    (@array [$($elems:expr),*]) => {
+        $crate::__private::vec![$($elems),*]
+    };
+
+    // Next element is `null`.
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(null)] $($rest)*)
+    };
+
+    // Next element is `true`.
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(true)] $($rest)*)
+    };
+
+    // Next element is `false`.
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!(false)] $($rest)*)
+    };
+
+    // Next element is an array.
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!([$($array)*])] $($rest)*)
+    };
+
+    // Next element is a map.
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!({$($map)*})] $($rest)*)
+    };
+
+    // Next element is an expression followed by comma.
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($next),] $($rest)*)
+    };
+
+    // Last element is an expression with no trailing comma.
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elems,)* $crate::json_internal!($last)])
+    };
+
+    // Comma after the most recent element.
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // Unexpected token after most recent element.
+    (@array [$($elems:expr),*] $unexpected:tt $($rest:tt)*) => {
+        $crate::json_unexpected!($unexpected)
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // TT muncher for parsing the inside of an object {...}. Each entry is
+    // inserted into the given map variable.
+    //
+    // Must be invoked as: json_internal!(@object $map () ($($tt)*) ($($tt)*))
+    //
+    // We require two copies of the input tokens so that we can match on one
+    // copy and trigger errors on the other copy.
+    //////////////////////////////////////////////////////////////////////////
+
+    // Done.
+    (@object $object:ident () () ()) => {};
+
+    // Insert the current entry followed by trailing comma.
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        let _ = $object.insert($crate::json_internal!(@key $($key)+), $value);
+        $crate::json_internal!(@object $object () ($($rest)*) ($($rest)*));
+    };
+
+    // Current entry followed by unexpected token.
+    (@object $object:ident [$($key:tt)+] ($value:expr) $unexpected:tt $($rest:tt)*) => {
+        $crate::json_unexpected!($unexpected);
+    };
+
+    // Insert the last entry without trailing comma.
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {
+        let _ = $object.insert($crate::json_internal!(@key $($key)+), $value);
+    };
+
+    // Next value is `null`.
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!(null)) $($rest)*);
+    };
+
+    // Next value is `true`.
+    (@object $object:ident ($($key:tt)+) (: true $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!(true)) $($rest)*);
+    };
+
+    // Next value is `false`.
+    (@object $object:ident ($($key:tt)+) (: false $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!(false)) $($rest)*);
+    };
+
+    // Next value is an array.
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!([$($array)*])) $($rest)*);
+    };
+
+    // Next value is a map.
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!({$($map)*})) $($rest)*);
+    };
+
+    // Next value is an expression followed by comma.
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!($value)) , $($rest)*);
+    };
+
+    // Last value is an expression with no trailing comma.
+    (@object $object:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!($value)));
+    };
+
+    // Missing value for last entry. Trigger a reasonable error message.
+    (@object $object:ident ($($key:tt)+) (:) $copy:tt) => {
+        // "unexpected end of macro invocation"
+        $crate::json_internal!();
+    };
+
+    // Missing colon and value for last entry. Trigger a reasonable error
+    // message.
+    (@object $object:ident ($($key:tt)+) () $copy:tt) => {
+        // "unexpected end of macro invocation"
+        $crate::json_internal!();
+    };
+
+    // Misplaced colon. Trigger a reasonable error message.
+    (@object $object:ident () (: $($rest:tt)*) ($colon:tt $($copy:tt)*)) => {
+        // Takes no arguments so "no rules expected the token `:`".
+        $crate::json_unexpected!($colon);
+    };
+
+    // Found a comma inside a key. Trigger a reasonable error message.
+    (@object $object:ident ($($key:tt)*) (, $($rest:tt)*) ($comma:tt $($copy:tt)*)) => {
+        // Takes no arguments so "no rules expected the token `,`".
+        $crate::json_unexpected!($comma);
+    };
+
+    // Key is fully parenthesized. This avoids clippy double_parens false
+    // positives because the parenthesization may be necessary here.
+    (@object $object:ident () (($key:expr) : $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($key) (: $($rest)*) (: $($rest)*));
+    };
+
+    // Refuse to absorb colon token into key expression.
+    (@object $object:ident ($($key:tt)*) (: $($unexpected:tt)+) $copy:tt) => {
+        $crate::json_expect_expr_comma!($($unexpected)+);
+    };
+
+    // Munch a token into the current key.
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // Object keys. String literals reference static memory.
+    //////////////////////////////////////////////////////////////////////////
+
+    (@key $key:literal) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::LiteralKind;
+        $crate::__private::Literal($key).into_key()
+    }};
+
+    (@key $($key:tt)+) => {
+        match ($($key)+) {
+            key => {
+                #[allow(unused_imports)]
+                use $crate::__private::{ByteStringKind, StringKind};
+                (&key).key_kind().into_key(key)
+            }
+        }
+    };
+
+    //////////////////////////////////////////////////////////////////////////
+    // The main implementation.
+    //
+    // Must be invoked as: json_internal!($($json)+)
+    //////////////////////////////////////////////////////////////////////////
+
+    (null) => {
+        $crate::Value::Null
+    };
+
+    (true) => {
+        $crate::Value::Bool(true)
+    };
+
+    (false) => {
+        $crate::Value::Bool(false)
+    };
+
+    ([]) => {
+        $crate::Value::Array($crate::__private::vec![])
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::json_internal!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::Value::Object($crate::Map::new())
+    };
+
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Object({
+            let mut object = $crate::Map::new();
+            $crate::json_internal!(@object object () ($($tt)+) ($($tt)+));
+            object
+        })
+    };
+
+    // Any expression converting into a `Value`, `Bytes`, or any Serialize
+    // type. Must be below every other rule.
+    ($other:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaBytes, ViaInto, ViaSerialize};
+        $crate::__private::Interpolated($other).into_value()
+    }};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_unexpected {
+    () => {};
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_expect_expr_comma {
+    ($e:expr , $($tt:tt)*) => {};
+}
+
+/// Support for the `json!` macro.
+///
+/// An interpolated expression is wrapped in `Interpolated`, and the
+/// conversion is picked with autoref specialization: method lookup tries the
+/// wrapper by value, then by reference, then by mutable reference, so the
+/// first of `ViaInto`, `ViaBytes` and `ViaSerialize` that applies is used.
+///
+/// Keys are converted the same way: `key_kind` is looked up on a reference
+/// to the key, and returns a tag converting it into a `ByteString` directly
+/// if it can, or through a `String` otherwise. String literals use the
+/// inherent `Literal::into_key`, which takes precedence over `LiteralKind`
+/// for other literals like chars.
+#[doc(hidden)]
+pub mod __private {
+    use crate::lib::String;
+    use crate::{ByteString, Value};
+    #[cfg(not(feature = "std"))]
+    pub use alloc::vec;
+    use bytes::Bytes;
+    use serde::Serialize;
+    #[cfg(feature = "std")]
+    pub use std::vec;
+
+    pub struct Interpolated<T>(pub T);
+
+    pub trait ViaInto {
+        fn into_value(self) -> Value;
+    }
+
+    impl<T: Into<Value>> ViaInto for Interpolated<T> {
+        fn into_value(self) -> Value {
+            self.0.into()
+        }
+    }
+
+    pub trait ViaBytes {
+        fn into_value(self) -> Value;
+    }
+
+    impl ViaBytes for &Interpolated<Bytes> {
+        fn into_value(self) -> Value {
+            match ByteString::try_from(self.0.clone()) {
+                Ok(s) => Value::String(s),
+                Err(err) => panic!("interpolated Bytes are not a string: {err}"),
+            }
+        }
+    }
+
+    pub trait ViaSerialize {
+        fn into_value(self) -> Value;
+    }
+
+    impl<T: Serialize> ViaSerialize for &mut Interpolated<T> {
+        fn into_value(self) -> Value {
+            crate::to_value(&self.0).unwrap()
+        }
+    }
+
+    pub struct Literal<T>(pub T);
+
+    impl Literal<&'static str> {
+        pub fn into_key(self) -> ByteString {
+            ByteString::from_static(self.0)
+        }
+    }
+
+    pub trait LiteralKind {
+        fn into_key(self) -> ByteString;
+    }
+
+    impl<T: Into<String>> LiteralKind for Literal<T> {
+        fn into_key(self) -> ByteString {
+            ByteString::from(self.0.into())
+        }
+    }
+
+    pub struct ByteStringKey;
+
+    impl ByteStringKey {
+        pub fn into_key<T: Into<ByteString>>(self, key: T) -> ByteString {
+            key.into()
+        }
+    }
+
+    pub trait ByteStringKind {
+        fn key_kind(&self) -> ByteStringKey {
+            ByteStringKey
+        }
+    }
+
+    impl<T: Into<ByteString>> ByteStringKind for T {}
+
+    pub struct StringKey;
+
+    impl StringKey {
+        pub fn into_key<T: Into<String>>(self, key: T) -> ByteString {
+            ByteString::from(key.into())
+        }
+    }
+
+    pub trait StringKind {
+        fn key_kind(&self) -> StringKey {
+            StringKey
+        }
+    }
+
+    impl<T: Into<String>> StringKind for &T {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ByteString, Map, Value};
+    use bytes::Bytes;
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn literals() {
+        let value = json!({
+            "null": null,
+            "bools": [true, false,],
+            "number": -1.5,
+            "nested": {"array": [[], {}, [1, [2]]], "empty": {}},
+            ("computed".to_owned() + "key"): 3,
+            "trailing": "comma",
+        });
+        let expected = Value::from_static(
+            r#"{"null": null, "bools": [true, false], "number": -1.5,
+            "nested": {"array": [[], {}, [1, [2]]], "empty": {}},
+            "computedkey": 3, "trailing": "comma"}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(json!([]), Value::Array(Vec::new()));
+        assert_eq!(json!(null), Value::Null);
+    }
+
+    #[test]
+    fn interpolation() {
+        let long = "a string long enough not to be stored inline".to_owned();
+        let string = ByteString::from(long.as_str());
+        let bytes = Bytes::from(long.clone());
+        let value = Value::from(vec![1, 2]);
+        let mut map = Map::new();
+        map.insert("k", json!(1));
+        let serialized = BTreeMap::from([("b", 'c')]);
+
+        let (string_ptr, bytes_ptr) = (string.as_ptr(), bytes.as_ptr());
+        let key = ByteString::from("key");
+        let result = json!({
+            key: [string, bytes],
+            "value": value,
+            "map": map,
+            "serialized": serialized,
+            "option": Some(2u8),
+        });
+        assert_eq!(
+            result,
+            Value::from_static(
+                r#"{"key": ["a string long enough not to be stored inline",
+                "a string long enough not to be stored inline"],
+                "value": [1, 2], "map": {"k": 1}, "serialized": {"b": "c"}, "option": 2}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(result["key"][0].as_str().unwrap().as_ptr(), string_ptr);
+        assert_eq!(result["key"][1].as_str().unwrap().as_ptr(), bytes_ptr);
+    }
+
+    #[test]
+    fn keys() {
+        let owned = "owned".to_owned();
+        let value = json!({
+            (&owned): 1,
+            (Cow::Borrowed("cow")): 2,
+            'c': 3,
+            (ByteString::from("bytes")): 4,
+            "literal": 5,
+        });
+        assert_eq!(
+            value,
+            Value::from_static(r#"{"owned": 1, "cow": 2, "c": 3, "bytes": 4, "literal": 5}"#)
+                .unwrap()
+        );
+        assert_eq!(owned, "owned");
+    }
+
+    #[test]
+    #[should_panic = "interpolated Bytes are not a string"]
+    fn invalid_bytes() {
+        json!(Bytes::from_static(b"\xFF"));
+    }
+}
@@ -172,7 +172,7 @@ impl Map<ByteString, Value> {
 
     /// Gets the given key's corresponding entry in the map for in-place
     /// manipulation.
-    pub fn entry<S>(&mut self, key: S) -> Entry
+    pub fn entry<S>(&mut self, key: S) -> Entry<'_>
     where
         S: Into<ByteString>,
     {
@@ -201,7 +201,7 @@ impl Map<ByteString, Value> {
 
     /// Gets an iterator over the entries of the map.
     #[inline]
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
             iter: self.map.iter(),
         }
@@ -209,7 +209,7 @@ impl Map<ByteString, Value> {
 
     /// Gets a mutable iterator over the entries of the map.
     #[inline]
-    pub fn iter_mut(&mut self) -> IterMut {
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
         IterMut {
             iter: self.map.iter_mut(),
         }
@@ -217,7 +217,7 @@ impl Map<ByteString, Value> {
 
     /// Gets an iterator over the keys of the map.
     #[inline]
-    pub fn keys(&self) -> Keys {
+    pub fn keys(&self) -> Keys<'_> {
         Keys {
             iter: self.map.keys(),
         }
@@ -225,7 +225,7 @@ impl Map<ByteString, Value> {
 
     /// Gets an iterator over the values of the map.
     #[inline]
-    pub fn values(&self) -> Values {
+    pub fn values(&self) -> Values<'_> {
         Values {
             iter: self.map.values(),
         }
@@ -233,7 +233,7 @@ impl Map<ByteString, Value> {
 
     /// Gets an iterator over mutable values of the map.
     #[inline]
-    pub fn values_mut(&mut self) -> ValuesMut {
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
         ValuesMut {
             iter: self.map.values_mut(),
         }
@@ -402,7 +402,7 @@ impl<'de> de::Deserialize<'de> for Map<ByteString, Value> {
                 let mut values = Map::new();
 
                 while let Some((key, value)) = tri!(visitor.next_entry()) {
-                    let k: String = key;
+                    let k: ByteString = key;
                     values.insert(k, value);
                 }
 
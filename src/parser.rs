@@ -0,0 +1,1068 @@
+//! Resumable JSON parser over a sequence of `Bytes` chunks.
+//!
+//! The parser does not build any tree, it produces a stream of [`Event`]s.
+//! Strings that fit in a single chunk are returned as slices of that chunk,
+//! strings that span a chunk boundary are copied.
+
+use crate::lib::*;
+use crate::value::{LimitExceeded, ParseOptions};
+use crate::ByteString;
+use bytes::{Bytes, BytesMut};
+use serde::de::Error as _;
+use serde_json::{Error, Number};
+
+/// Maximum nesting depth, matching the default recursion limit of serde_json.
+const MAX_DEPTH: usize = 128;
+
+/// Capacity of the buffers allocated by [`Arena`].
+const ARENA_BLOCK: usize = 4096;
+
+/// UTF-8 byte order mark, skipped at the start of the input in lenient mode.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// A token of a JSON document, returned by
+/// [`EventReader`](crate::value::EventReader).
+///
+/// Strings and keys reference the input when they can, like the strings of a
+/// [`Value`](crate::Value) parsed from it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// `{`
+    StartObject,
+    /// `}`
+    EndObject,
+    /// `[`
+    StartArray,
+    /// `]`
+    EndArray,
+    /// An object key, always followed by the events of its value.
+    Key(ByteString),
+    String(ByteString),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Expecting the top level value.
+    Start,
+    /// After `[`.
+    ArrayFirst,
+    /// After `,` in an array.
+    ArrayNext,
+    /// After `{`.
+    ObjectFirst,
+    /// After `,` in an object.
+    ObjectNext,
+    /// After an object key.
+    Colon,
+    /// After `:`.
+    ObjectValue,
+    /// After a value nested in an array or object.
+    AfterValue,
+    /// The top level value is complete.
+    Done,
+}
+
+/// Input skipped in lenient mode, which can span several chunks.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Skip {
+    /// The first bytes of a byte order mark.
+    Bom(usize),
+    /// After a `/` starting a comment.
+    Slash,
+    /// In a `//` comment.
+    LineComment,
+    /// In a `/* */` comment, right after a `*` or not.
+    BlockComment { star: bool },
+}
+
+/// Position in the grammar of a number, after the bytes scanned so far.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NumberState {
+    Start,
+    /// After `-`.
+    Minus,
+    /// After a leading `0`.
+    Zero,
+    Integer,
+    /// After `.`.
+    Point,
+    Fraction,
+    /// After `e` or `E`.
+    Exponent,
+    /// After the sign of the exponent.
+    ExponentSign,
+    ExponentDigits,
+    /// After `0x`, in lenient mode.
+    Hex,
+}
+
+impl NumberState {
+    /// Returns the state after `b`, or `None` if `b` cannot continue the
+    /// number.
+    fn next(self, b: u8, lenient: bool) -> Option<Self> {
+        use NumberState::*;
+
+        Some(match (self, b) {
+            (Start, b'-') => Minus,
+            (Start | Minus, b'0') => Zero,
+            (Start | Minus, b'1'..=b'9') => Integer,
+            (Integer, b'0'..=b'9') => Integer,
+            (Zero | Integer, b'.') => Point,
+            (Point | Fraction, b'0'..=b'9') => Fraction,
+            (Zero | Integer | Fraction, b'e' | b'E') => Exponent,
+            (Exponent, b'+' | b'-') => ExponentSign,
+            (Exponent | ExponentSign | ExponentDigits, b'0'..=b'9') => ExponentDigits,
+            (Zero, b'x' | b'X') if lenient => Hex,
+            (Hex, b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F') => Hex,
+            _ => return None,
+        })
+    }
+}
+
+/// A token that started in a previous chunk. Its bytes are in `scratch`.
+enum Pending {
+    String {
+        key: bool,
+        escaped: bool,
+        /// The last byte of the previous chunk was a backslash.
+        in_escape: bool,
+    },
+    Number(NumberState),
+    Literal {
+        literal: &'static [u8],
+        matched: usize,
+    },
+    /// An unquoted object key, in lenient mode.
+    Identifier,
+}
+
+pub(crate) struct Parser {
+    options: ParseOptions,
+    chunk: Bytes,
+    pos: usize,
+    eof: bool,
+    stack: Vec<Container>,
+    /// Number of values in each container of `stack`.
+    lengths: Vec<usize>,
+    /// Number of values started so far.
+    nodes: usize,
+    state: State,
+    pending: Option<Pending>,
+    /// Comment or byte order mark being skipped.
+    skip: Option<Skip>,
+    /// Quote of the current string, `'` is only accepted in lenient mode.
+    quote: u8,
+    scratch: Vec<u8>,
+    /// Strings decoded by `decode`.
+    decoded: Vec<u8>,
+    arena: Option<Arena>,
+
+    /// Absolute offset of the start of the current string or number.
+    token_start: usize,
+    /// Absolute offset of the start of `chunk`.
+    offset: usize,
+    /// Absolute offset of the start of the input, see `start_at`.
+    origin: usize,
+    /// Line of `chunk[counted]`, starting at 1.
+    line: usize,
+    /// Absolute offset of the start of `line`.
+    line_start: usize,
+    /// Newlines were counted in `chunk[..counted]`.
+    counted: usize,
+}
+
+impl Parser {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Parser {
+            arena: options.arena.then(Arena::new),
+            skip: options.lenient.then_some(Skip::Bom(0)),
+            options,
+            chunk: Bytes::new(),
+            pos: 0,
+            eof: false,
+            stack: Vec::new(),
+            lengths: Vec::new(),
+            nodes: 0,
+            state: State::Start,
+            pending: None,
+            quote: b'"',
+            scratch: Vec::new(),
+            decoded: Vec::new(),
+            token_start: 0,
+            offset: 0,
+            origin: 0,
+            line: 1,
+            line_start: 0,
+            counted: 0,
+        }
+    }
+
+    /// Adds the next chunk of input.
+    ///
+    /// The events of the previous chunk must have been consumed with `next`.
+    /// The chunk is rejected if it goes over the maximum input size.
+    pub(crate) fn feed(&mut self, chunk: Bytes) -> Result<(), Error> {
+        debug_assert_eq!(self.pos, self.chunk.len(), "previous chunk not consumed");
+        self.count_lines(self.chunk.len());
+        self.offset += self.chunk.len();
+        self.chunk = chunk;
+        self.pos = 0;
+        self.counted = 0;
+
+        match self.options.max_size {
+            Some(max) if self.offset - self.origin + self.chunk.len() > max => {
+                self.pos = self.origin + max - self.offset;
+                Err(self.error(&LimitExceeded::Size(max).to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reports positions in error messages as if the input started at
+    /// `line` and `column` instead of 1 and 1, for documents that are part
+    /// of a larger input.
+    pub(crate) fn start_at(&mut self, line: usize, column: usize) {
+        debug_assert_eq!(self.offset + self.chunk.len(), 0, "input already fed");
+        self.line = line;
+        self.origin = column - 1;
+        self.offset = self.origin;
+    }
+
+    /// Marks the end of the input. The remaining events can then be
+    /// obtained with `next`.
+    pub(crate) fn end(&mut self) {
+        self.eof = true;
+    }
+
+    /// Returns the next event, or `None` if more input is needed or, after
+    /// `end`, if the document is complete.
+    pub(crate) fn next(&mut self) -> Result<Option<Event>, Error> {
+        if let Some(pending) = self.pending.take() {
+            return self.resume(pending);
+        }
+
+        loop {
+            tri!(self.skip_whitespace());
+            let Some(&byte) = self.chunk.get(self.pos) else {
+                return if self.eof {
+                    self.end_of_input()
+                } else {
+                    Ok(None)
+                };
+            };
+
+            match self.state {
+                State::Start | State::ArrayNext | State::ObjectValue => {
+                    if byte == b']' && self.state == State::ArrayNext {
+                        if self.options.lenient {
+                            return Ok(Some(self.close()));
+                        }
+                        return Err(self.error("trailing comma"));
+                    }
+                    return self.value(byte);
+                }
+                State::ArrayFirst => {
+                    if byte == b']' {
+                        return Ok(Some(self.close()));
+                    }
+                    return self.value(byte);
+                }
+                State::ObjectFirst | State::ObjectNext => {
+                    return match byte {
+                        b'"' => self.string(true, b'"'),
+                        b'}' if self.state == State::ObjectFirst || self.options.lenient => {
+                            Ok(Some(self.close()))
+                        }
+                        b'}' => Err(self.error("trailing comma")),
+                        b'\'' if self.options.lenient => self.string(true, b'\''),
+                        b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$' if self.options.lenient => {
+                            self.identifier()
+                        }
+                        _ => Err(self.error("key must be a string")),
+                    };
+                }
+                State::Colon => {
+                    if byte != b':' {
+                        return Err(self.error("expected `:`"));
+                    }
+                    self.pos += 1;
+                    self.state = State::ObjectValue;
+                }
+                State::AfterValue => match (self.stack.last(), byte) {
+                    (Some(Container::Array), b',') => {
+                        self.pos += 1;
+                        self.state = State::ArrayNext;
+                    }
+                    (Some(Container::Object), b',') => {
+                        self.pos += 1;
+                        self.state = State::ObjectNext;
+                    }
+                    (Some(Container::Array), b']') | (Some(Container::Object), b'}') => {
+                        return Ok(Some(self.close()));
+                    }
+                    (Some(Container::Array), _) => {
+                        return Err(self.error("expected `,` or `]`"));
+                    }
+                    _ => return Err(self.error("expected `,` or `}`")),
+                },
+                State::Done => return Err(self.error("trailing characters")),
+            }
+        }
+    }
+
+    /// Skips whitespace, and in lenient mode comments and a leading byte
+    /// order mark.
+    fn skip_whitespace(&mut self) -> Result<(), Error> {
+        while let Some(&byte) = self.chunk.get(self.pos) {
+            match self.skip {
+                None => match byte {
+                    b' ' | b'\n' | b'\t' | b'\r' => {}
+                    b'/' if self.options.lenient => self.skip = Some(Skip::Slash),
+                    _ => return Ok(()),
+                },
+                Some(Skip::Bom(matched)) => {
+                    if byte == BOM[matched] {
+                        self.skip = (matched + 1 < BOM.len()).then_some(Skip::Bom(matched + 1));
+                    } else if matched == 0 {
+                        self.skip = None;
+                        continue;
+                    } else {
+                        return Err(self.error("expected value"));
+                    }
+                }
+                Some(Skip::Slash) => match byte {
+                    b'/' => self.skip = Some(Skip::LineComment),
+                    b'*' => self.skip = Some(Skip::BlockComment { star: false }),
+                    _ => return Err(self.error("expected `/` or `*` after `/`")),
+                },
+                Some(Skip::LineComment) => {
+                    if byte == b'\n' {
+                        self.skip = None;
+                    }
+                }
+                Some(Skip::BlockComment { star }) => {
+                    self.skip = if star && byte == b'/' {
+                        None
+                    } else {
+                        Some(Skip::BlockComment { star: byte == b'*' })
+                    };
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, byte: u8) -> Result<Option<Event>, Error> {
+        tri!(self.count_value());
+        match byte {
+            b'{' => self.open(Container::Object),
+            b'[' => self.open(Container::Array),
+            b'"' => self.string(false, b'"'),
+            b'\'' if self.options.lenient => self.string(false, b'\''),
+            b'-' | b'0'..=b'9' => self.number(),
+            b't' => self.literal(b"true", 0),
+            b'f' => self.literal(b"false", 0),
+            b'n' => self.literal(b"null", 0),
+            _ => Err(self.error("expected value")),
+        }
+    }
+
+    /// Checks the limits on the number of values, before parsing a new one.
+    fn count_value(&mut self) -> Result<(), Error> {
+        self.nodes += 1;
+        if let Some(max) = self.options.max_nodes {
+            if self.nodes > max {
+                return Err(self.error(&LimitExceeded::Nodes(max).to_string()));
+            }
+        }
+
+        let (Some(&container), Some(len)) = (self.stack.last(), self.lengths.last_mut()) else {
+            return Ok(());
+        };
+        *len += 1;
+        let len = *len;
+        match container {
+            Container::Array => match self.options.max_array_length {
+                Some(max) if len > max => {
+                    Err(self.error(&LimitExceeded::ArrayLength(max).to_string()))
+                }
+                _ => Ok(()),
+            },
+            Container::Object => match self.options.max_object_keys {
+                Some(max) if len > max => {
+                    Err(self.error(&LimitExceeded::ObjectKeys(max).to_string()))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn open(&mut self, container: Container) -> Result<Option<Event>, Error> {
+        match self.options.max_depth {
+            Some(max) if self.stack.len() >= max => {
+                return Err(self.error(&LimitExceeded::Depth(max).to_string()));
+            }
+            None if self.stack.len() >= MAX_DEPTH => {
+                return Err(self.error("recursion limit exceeded"));
+            }
+            _ => {}
+        }
+        self.pos += 1;
+        self.stack.push(container);
+        self.lengths.push(0);
+        Ok(Some(match container {
+            Container::Array => {
+                self.state = State::ArrayFirst;
+                Event::StartArray
+            }
+            Container::Object => {
+                self.state = State::ObjectFirst;
+                Event::StartObject
+            }
+        }))
+    }
+
+    fn close(&mut self) -> Event {
+        self.pos += 1;
+        let container = self.stack.pop();
+        self.lengths.pop();
+        self.after_value();
+        match container {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.state = if self.stack.is_empty() {
+            State::Done
+        } else {
+            State::AfterValue
+        };
+    }
+
+    fn scalar(&mut self, event: Event) -> Result<Option<Event>, Error> {
+        self.after_value();
+        Ok(Some(event))
+    }
+
+    fn string(&mut self, key: bool, quote: u8) -> Result<Option<Event>, Error> {
+        self.token_start = self.offset + self.pos;
+        self.quote = quote;
+        let start = self.pos + 1;
+        let (end, escaped) = tri!(self.scan_string(start, false));
+        tri!(self.check_string_length(end.unwrap_or(self.chunk.len()) - start, true));
+        match end {
+            Some(end) => {
+                self.pos = end + 1;
+                let chunk = self.chunk.clone();
+                let raw = &chunk[start..end];
+                let string = match str::from_utf8(raw) {
+                    Ok(s) if !escaped => match self.interned(key, s) {
+                        Some(string) => string,
+                        None if quote == b'"' => ByteString::new_escape_free(&chunk, s),
+                        None => ByteString::new(&chunk, s),
+                    },
+                    // single quoted strings cannot be written back verbatim, and
+                    // keys are decoded anyway when the map hashes or compares
+                    // them
+                    _ if escaped && !key && quote == b'"' && self.options.defer_unescape => {
+                        tri!(self.check_escapes(raw));
+                        ByteString::escaped(chunk.slice(start..end)).within(chunk.len())
+                    }
+                    _ => tri!(self.owned_string(raw, key, escaped, !escaped && quote == b'"')),
+                };
+                self.string_event(key, string)
+            }
+            None => {
+                self.scratch.clear();
+                self.scratch.extend_from_slice(&self.chunk[start..]);
+                let in_escape = self.pos > self.chunk.len();
+                self.pos = self.chunk.len();
+                self.suspend(Pending::String {
+                    key,
+                    escaped,
+                    in_escape,
+                })
+            }
+        }
+    }
+
+    /// Looks for the closing quote of a string starting at `self.chunk[start]`.
+    ///
+    /// Returns its index if it is in the current chunk, and whether the
+    /// string contains escape sequences. If the end of the chunk is reached,
+    /// `self.pos` is left past the end of the chunk if the last byte was a
+    /// backslash.
+    fn scan_string(
+        &mut self,
+        start: usize,
+        in_escape: bool,
+    ) -> Result<(Option<usize>, bool), Error> {
+        let bytes = &self.chunk[..];
+        let mut escaped = false;
+        let mut i = start + in_escape as usize;
+        while i < bytes.len() {
+            match bytes[i] {
+                byte if byte == self.quote => return Ok((Some(i), escaped)),
+                b'\\' => {
+                    escaped = true;
+                    i += 2;
+                }
+                0x00..=0x1F => {
+                    self.pos = i;
+                    return Err(self.error(
+                        "control character (\\u0000-\\u001F) found while parsing a string",
+                    ));
+                }
+                _ => i += 1,
+            }
+        }
+        self.pos = i;
+        Ok((None, escaped))
+    }
+
+    /// `len` is the length of the string so far, escape sequences included.
+    fn check_string_length(&mut self, len: usize, quoted: bool) -> Result<(), Error> {
+        match self.options.max_string_length {
+            Some(max) if len > max => {
+                let code = LimitExceeded::StringLength(max).to_string();
+                // point to the first byte over the limit, after the quote
+                Err(self.token_error(&code, max + 1 + quoted as usize))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Decodes a string that cannot reference the input, because it contains
+    /// escape sequences or spans several chunks.
+    ///
+    /// `escape_free` is set for strings that contain no character escaped in
+    /// JSON, like double quoted strings without escape sequences.
+    fn owned_string(
+        &mut self,
+        raw: &[u8],
+        key: bool,
+        escaped: bool,
+        escape_free: bool,
+    ) -> Result<ByteString, Error> {
+        let mut decoded = mem::take(&mut self.decoded);
+        let result = self.decode(raw, escaped, &mut decoded).map(|s| {
+            if let Some(string) = self.interned(key, s) {
+                return string;
+            }
+            match &mut self.arena {
+                Some(arena) => arena.alloc(s, escape_free),
+                None if escape_free => {
+                    ByteString::escape_free(Bytes::copy_from_slice(s.as_bytes()))
+                }
+                None => ByteString::from(s),
+            }
+        });
+        self.decoded = decoded;
+        result
+    }
+
+    /// Returns the shared copy of a key, if keys are interned.
+    fn interned(&self, key: bool, s: &str) -> Option<ByteString> {
+        match &self.options.key_interner {
+            Some(interner) if key => Some(interner.intern(s)),
+            _ => None,
+        }
+    }
+
+    /// Validates a string that is kept escaped.
+    fn check_escapes(&mut self, raw: &[u8]) -> Result<(), Error> {
+        let mut decoded = mem::take(&mut self.decoded);
+        let result = self.decode(raw, true, &mut decoded).map(drop);
+        self.decoded = decoded;
+        result
+    }
+
+    /// Returns the content of a string, unescaped into `out` if needed.
+    fn decode<'a>(
+        &mut self,
+        raw: &'a [u8],
+        escaped: bool,
+        out: &'a mut Vec<u8>,
+    ) -> Result<&'a str, Error> {
+        let bytes = if escaped {
+            out.clear();
+            tri!(self.unescape(raw, out));
+            &out[..]
+        } else {
+            raw
+        };
+        str::from_utf8(bytes).map_err(|err| self.utf8_error(err))
+    }
+
+    fn unescape(&mut self, raw: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+        let single_quoted = self.quote == b'\'';
+        unescape(raw, out, single_quoted)
+            .map_err(|(code, consumed)| self.token_error(code, 1 + consumed))
+    }
+
+    fn utf8_error(&mut self, err: str::Utf8Error) -> Error {
+        self.token_error("invalid unicode code point", 2 + err.valid_up_to())
+    }
+
+    fn string_event(&mut self, key: bool, string: ByteString) -> Result<Option<Event>, Error> {
+        if key {
+            self.state = State::Colon;
+            Ok(Some(Event::Key(string)))
+        } else {
+            self.scalar(Event::String(string))
+        }
+    }
+
+    fn resume_string(
+        &mut self,
+        key: bool,
+        escaped: bool,
+        in_escape: bool,
+    ) -> Result<Option<Event>, Error> {
+        let start = self.pos;
+        if in_escape && start == self.chunk.len() {
+            return self.suspend(Pending::String {
+                key,
+                escaped,
+                in_escape,
+            });
+        }
+
+        let (end, more_escapes) = tri!(self.scan_string(start, in_escape));
+        let len = self.scratch.len() + end.unwrap_or(self.chunk.len()) - start;
+        tri!(self.check_string_length(len, true));
+        let escaped = escaped || more_escapes || in_escape;
+        match end {
+            Some(end) => {
+                self.scratch.extend_from_slice(&self.chunk[start..end]);
+                self.pos = end + 1;
+                let raw = mem::take(&mut self.scratch);
+                let escape_free = !escaped && self.quote == b'"';
+                let string = self.owned_string(&raw, key, escaped, escape_free);
+                self.scratch = raw;
+                self.string_event(key, tri!(string))
+            }
+            None => {
+                self.scratch.extend_from_slice(&self.chunk[start..]);
+                let in_escape = self.pos > self.chunk.len();
+                self.pos = self.chunk.len();
+                self.suspend(Pending::String {
+                    key,
+                    escaped,
+                    in_escape,
+                })
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Option<Event>, Error> {
+        let start = self.pos;
+        self.token_start = self.offset + start;
+        let (end, state) = self.scan_number(start, NumberState::Start);
+        if end == self.chunk.len() && !self.eof {
+            self.scratch.clear();
+            self.scratch.extend_from_slice(&self.chunk[start..]);
+            self.pos = end;
+            return self.suspend(Pending::Number(state));
+        }
+
+        let number = self.parse_number(&self.chunk[start..end], state, self.chunk.get(end));
+        match number {
+            Ok(n) => {
+                self.pos = end;
+                self.scalar(Event::Number(n))
+            }
+            Err((code, consumed)) => Err(self.token_error(&code, consumed)),
+        }
+    }
+
+    fn resume_number(&mut self, state: NumberState) -> Result<Option<Event>, Error> {
+        let (end, state) = self.scan_number(self.pos, state);
+        self.scratch.extend_from_slice(&self.chunk[self.pos..end]);
+        self.pos = end;
+        if end == self.chunk.len() && !self.eof {
+            return self.suspend(Pending::Number(state));
+        }
+
+        let number = self.parse_number(&self.scratch, state, self.chunk.get(end));
+        match number {
+            Ok(n) => self.scalar(Event::Number(n)),
+            Err((code, consumed)) => Err(self.token_error(&code, consumed)),
+        }
+    }
+
+    /// Scans the bytes of a number from `start`, up to the first byte that
+    /// cannot continue it.
+    fn scan_number(&self, start: usize, mut state: NumberState) -> (usize, NumberState) {
+        let mut end = start;
+        while let Some(&b) = self.chunk.get(end) {
+            match state.next(b, self.options.lenient) {
+                Some(next) => state = next,
+                None => break,
+            }
+            end += 1;
+        }
+        (end, state)
+    }
+
+    /// Parses a scanned number, which ended in `state` before `next`.
+    fn parse_number(
+        &self,
+        raw: &[u8],
+        state: NumberState,
+        next: Option<&u8>,
+    ) -> Result<Number, (String, usize)> {
+        match (state, next) {
+            // serde_json expects a digit
+            (NumberState::Minus | NumberState::Point | NumberState::Exponent, Some(_))
+            | (NumberState::ExponentSign, Some(_))
+            | (NumberState::Zero, Some(b'0'..=b'9')) => {
+                return Err(("invalid number".to_owned(), raw.len() + 1));
+            }
+            (NumberState::Minus | NumberState::Point | NumberState::Exponent, None)
+            | (NumberState::ExponentSign, None) => {
+                return Err(("EOF while parsing a value".to_owned(), raw.len()));
+            }
+            _ => {}
+        }
+        if state == NumberState::Hex {
+            let digits = raw.strip_prefix(b"-").unwrap_or(raw);
+            if let Some(hex) = digits
+                .strip_prefix(b"0x")
+                .or_else(|| digits.strip_prefix(b"0X"))
+            {
+                return parse_hex_number(hex, raw.len() > digits.len());
+            }
+        }
+        parse_number(raw)
+    }
+
+    /// Parses an unquoted object key, in lenient mode.
+    fn identifier(&mut self) -> Result<Option<Event>, Error> {
+        let start = self.pos;
+        self.token_start = self.offset + start;
+        self.scratch.clear();
+        self.resume_identifier(start)
+    }
+
+    /// `scratch` holds the part of the identifier found in previous chunks.
+    fn resume_identifier(&mut self, start: usize) -> Result<Option<Event>, Error> {
+        let mut end = start;
+        while let Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'$') = self.chunk.get(end)
+        {
+            end += 1;
+        }
+        tri!(self.check_string_length(self.scratch.len() + end - start, false));
+        self.pos = end;
+        if end == self.chunk.len() && !self.eof {
+            self.scratch.extend_from_slice(&self.chunk[start..end]);
+            return self.suspend(Pending::Identifier);
+        }
+
+        let string = if self.scratch.is_empty() {
+            let chunk = self.chunk.clone();
+            let s = str::from_utf8(&chunk[start..end]).expect("identifiers are ASCII");
+            self.interned(true, s)
+                .unwrap_or_else(|| ByteString::new_escape_free(&chunk, s))
+        } else {
+            self.scratch.extend_from_slice(&self.chunk[start..end]);
+            let raw = mem::take(&mut self.scratch);
+            let string = self.owned_string(&raw, true, false, true);
+            self.scratch = raw;
+            tri!(string)
+        };
+        self.string_event(true, string)
+    }
+
+    fn literal(
+        &mut self,
+        literal: &'static [u8],
+        mut matched: usize,
+    ) -> Result<Option<Event>, Error> {
+        while matched < literal.len() {
+            match self.chunk.get(self.pos) {
+                Some(&byte) if byte == literal[matched] => {
+                    self.pos += 1;
+                    matched += 1;
+                }
+                Some(_) => return Err(self.error("expected ident")),
+                None if self.eof => return Err(self.eof_error("EOF while parsing a value")),
+                None => return self.suspend(Pending::Literal { literal, matched }),
+            }
+        }
+
+        self.scalar(match literal[0] {
+            b't' => Event::Bool(true),
+            b'f' => Event::Bool(false),
+            _ => Event::Null,
+        })
+    }
+
+    fn resume(&mut self, pending: Pending) -> Result<Option<Event>, Error> {
+        match pending {
+            Pending::String {
+                key,
+                escaped,
+                in_escape,
+            } => {
+                if self.chunk.len() == self.pos && self.eof {
+                    return Err(self.eof_error("EOF while parsing a string"));
+                }
+                self.resume_string(key, escaped, in_escape)
+            }
+            Pending::Number(state) => self.resume_number(state),
+            Pending::Literal { literal, matched } => self.literal(literal, matched),
+            Pending::Identifier => self.resume_identifier(self.pos),
+        }
+    }
+
+    fn suspend(&mut self, pending: Pending) -> Result<Option<Event>, Error> {
+        self.pending = Some(pending);
+        Ok(None)
+    }
+
+    fn end_of_input(&mut self) -> Result<Option<Event>, Error> {
+        if let Some(Skip::Slash | Skip::BlockComment { .. }) = self.skip {
+            return Err(self.eof_error("EOF while parsing a comment"));
+        }
+        let code = match (self.state, self.stack.last()) {
+            (State::Done, _) => return Ok(None),
+            (State::Start | State::ArrayNext | State::ObjectValue, _) => {
+                "EOF while parsing a value"
+            }
+            (_, Some(Container::Array)) => "EOF while parsing a list",
+            _ => "EOF while parsing an object",
+        };
+        Err(self.eof_error(code))
+    }
+
+    fn count_lines(&mut self, upto: usize) {
+        let upto = upto.min(self.chunk.len());
+        if upto <= self.counted {
+            return;
+        }
+        for (i, &byte) in self.chunk[self.counted..upto].iter().enumerate() {
+            if byte == b'\n' {
+                self.line += 1;
+                self.line_start = self.offset + self.counted + i + 1;
+            }
+        }
+        self.counted = upto;
+    }
+
+    /// Error at the current position.
+    #[cold]
+    fn error(&mut self, code: &str) -> Error {
+        self.count_lines(self.pos);
+        let column = self.offset + self.pos - self.line_start + 1;
+        Error::custom(format_args!("{code} at line {} column {column}", self.line))
+    }
+
+    /// Error after the first `consumed` bytes of the current token.
+    #[cold]
+    fn token_error(&mut self, code: &str, consumed: usize) -> Error {
+        // strings and numbers cannot contain newlines
+        self.count_lines(self.pos);
+        let column = self.token_start + consumed - self.line_start;
+        Error::custom(format_args!("{code} at line {} column {column}", self.line))
+    }
+
+    /// Error about the last object key returned by `next`, at its last
+    /// byte like serde_json reports errors about keys.
+    #[cold]
+    pub(crate) fn key_error(&mut self, code: &str) -> Error {
+        self.count_lines(self.pos);
+        let column = self.offset + self.pos - self.line_start;
+        Error::custom(format_args!("{code} at line {} column {column}", self.line))
+    }
+
+    /// Error at the end of the input.
+    #[cold]
+    fn eof_error(&mut self, code: &str) -> Error {
+        self.count_lines(self.chunk.len());
+        let column = self.offset + self.chunk.len() - self.line_start;
+        Error::custom(format_args!("{code} at line {} column {column}", self.line))
+    }
+}
+
+/// Parses the digits of a hexadecimal number, in lenient mode. `hex` only
+/// holds hexadecimal digits.
+fn parse_hex_number(hex: &[u8], negative: bool) -> Result<Number, (String, usize)> {
+    // the prefix, and the sign
+    let consumed = 2 + negative as usize;
+    if hex.is_empty() {
+        return Err(("invalid number".to_owned(), consumed + 1));
+    }
+    let digits = str::from_utf8(hex).expect("hexadecimal digits are ASCII");
+    let out_of_range = || ("number out of range".to_owned(), consumed + hex.len());
+    let value = tri!(u64::from_str_radix(digits, 16).map_err(|_| out_of_range()));
+    if !negative {
+        return Ok(value.into());
+    }
+    match 0i64.checked_sub_unsigned(value) {
+        Some(value) => Ok(value.into()),
+        None => Err(out_of_range()),
+    }
+}
+
+/// Buffers shared by the strings that cannot reference the input.
+///
+/// Each string is written after the previous one, and split off as a slice
+/// of the same allocation.
+struct Arena {
+    buf: BytesMut,
+}
+
+impl Arena {
+    fn new() -> Self {
+        Arena {
+            buf: BytesMut::new(),
+        }
+    }
+
+    fn alloc(&mut self, s: &str, escape_free: bool) -> ByteString {
+        if let Some(string) = ByteString::inline(s) {
+            return string;
+        }
+        // large strings get their own allocation instead of wasting the end
+        // of a block
+        let (bytes, block) = if s.len() > ARENA_BLOCK / 4 {
+            (Bytes::copy_from_slice(s.as_bytes()), s.len())
+        } else {
+            if self.buf.capacity() < s.len() {
+                self.buf = BytesMut::with_capacity(ARENA_BLOCK);
+            }
+            self.buf.extend_from_slice(s.as_bytes());
+            (self.buf.split().freeze(), ARENA_BLOCK)
+        };
+        let string = if escape_free {
+            ByteString::escape_free(bytes)
+        } else {
+            ByteString::from_validated(bytes)
+        };
+        string.within(block)
+    }
+}
+
+/// Parses a number with the same rules and precision as serde_json.
+///
+/// `raw` follows the JSON grammar, so the only errors are about the range of
+/// the number. On error, returns the error code and the number of bytes
+/// consumed.
+fn parse_number(raw: &[u8]) -> Result<Number, (String, usize)> {
+    // the number was scanned from ASCII characters only
+    let s = str::from_utf8(raw).expect("numbers are ASCII");
+    s.parse::<Number>().map_err(|err| {
+        // keep the error code, the position is relative to the number
+        let message = err.to_string();
+        let suffix = format!(" at line {} column {}", err.line(), err.column());
+        let code = match message.strip_suffix(&suffix) {
+            Some(code) => code.to_owned(),
+            None => message,
+        };
+        (code, err.column())
+    })
+}
+
+/// Decodes the escape sequences of the raw content of a JSON string.
+///
+/// The output is not validated as UTF-8. On error, returns the error code and
+/// the number of bytes consumed, like serde_json reports it.
+///
+/// `\'` is only accepted in `single_quoted` strings, which are a lenient
+/// mode extension.
+pub(crate) fn unescape(
+    raw: &[u8],
+    out: &mut Vec<u8>,
+    single_quoted: bool,
+) -> Result<(), (&'static str, usize)> {
+    let mut i = 0;
+    while i < raw.len() {
+        let byte = raw[i];
+        match byte {
+            b'\\' => {
+                let Some(&escape) = raw.get(i + 1) else {
+                    return Err(("EOF while parsing a string", i + 1));
+                };
+                i += 2;
+                match escape {
+                    b'"' => out.push(b'"'),
+                    b'\'' if single_quoted => out.push(b'\''),
+                    b'\\' => out.push(b'\\'),
+                    b'/' => out.push(b'/'),
+                    b'b' => out.push(b'\x08'),
+                    b'f' => out.push(b'\x0c'),
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'u' => {
+                        let c = tri!(decode_hex(raw, i));
+                        i += 4;
+                        let c = match c {
+                            0xDC00..=0xDFFF => {
+                                return Err(("lone leading surrogate in hex escape", i))
+                            }
+                            0xD800..=0xDBFF => {
+                                if raw.get(i) != Some(&b'\\') {
+                                    return Err(("unexpected end of hex escape", i + 1));
+                                }
+                                if raw.get(i + 1) != Some(&b'u') {
+                                    return Err(("unexpected end of hex escape", i + 2));
+                                }
+                                let c2 = tri!(decode_hex(raw, i + 2));
+                                i += 6;
+                                if !(0xDC00..=0xDFFF).contains(&c2) {
+                                    return Err(("lone leading surrogate in hex escape", i));
+                                }
+                                0x10000 + (((c - 0xD800) << 10) | (c2 - 0xDC00))
+                            }
+                            c => c,
+                        };
+                        // surrogates were handled above
+                        let c = char::from_u32(c).expect("valid code point");
+                        out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    _ => return Err(("invalid escape", i)),
+                }
+            }
+            0x00..=0x1F => {
+                return Err((
+                    "control character (\\u0000-\\u001F) found while parsing a string",
+                    i + 1,
+                ))
+            }
+            _ => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decode_hex(raw: &[u8], start: usize) -> Result<u32, (&'static str, usize)> {
+    let mut n = 0;
+    for i in start..start + 4 {
+        let value = match raw.get(i) {
+            Some(&digit @ b'0'..=b'9') => digit - b'0',
+            Some(&digit @ b'a'..=b'f') => digit - b'a' + 10,
+            Some(&digit @ b'A'..=b'F') => digit - b'A' + 10,
+            // serde_json reads the four bytes before validating them
+            _ => return Err(("invalid escape", start + 4)),
+        };
+        n = (n << 4) | value as u32;
+    }
+    Ok(n)
+}
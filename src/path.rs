@@ -11,6 +11,7 @@ use jsonpath_rust::parser::{
 };
 use regex::Regex;
 
+use crate::value::LazyValue;
 use crate::Value;
 
 #[derive(Clone)]
@@ -19,7 +20,7 @@ pub struct JsonPathInst {
 }
 
 impl JsonPathInst {
-    pub fn new(path: &str) -> Result<Self, JsonPathParserError> {
+    pub fn new(path: &str) -> Result<Self, JsonPathParserError<'_>> {
         Ok(JsonPathInst {
             path: parse_json_path(path)?,
         })
@@ -53,6 +54,55 @@ impl JsonPathInst {
             Value::Array(v)
         }
     }
+
+    /// Looks up the leading keys and indexes of the path without parsing,
+    /// then evaluates the rest of the path on the value they lead to.
+    pub(crate) fn select_lazy(&self, value: &LazyValue) -> Vec<Value> {
+        let chain = match &self.path {
+            JsonPath::Chain(chain) if matches!(chain.first(), Some(JsonPath::Root)) => &chain[1..],
+            _ => return self.select_materialized(value),
+        };
+
+        let mut target = value.clone();
+        let mut rest = chain;
+        while let Some((segment, tail)) = rest.split_first() {
+            let next = match segment {
+                JsonPath::Field(f) => target.get(f.as_str()),
+                JsonPath::Index(JsonPathIndex::Single(index)) => match index.as_u64() {
+                    Some(index) => target.get(index as usize),
+                    None => break,
+                },
+                _ => break,
+            };
+            match next {
+                Some(next) => target = next,
+                None => return Vec::new(),
+            }
+            rest = tail;
+        }
+
+        // filters can refer to the root of the document
+        if rest
+            .iter()
+            .any(|p| matches!(p, JsonPath::Index(JsonPathIndex::Filter(_))))
+        {
+            return self.select_materialized(value);
+        }
+        let target = match target.to_value() {
+            Ok(target) => target,
+            Err(_) => return Vec::new(),
+        };
+        select_chain(rest, &target, &target, None)
+            .map(|(_, value)| value.into_owned())
+            .collect()
+    }
+
+    fn select_materialized(&self, value: &LazyValue) -> Vec<Value> {
+        match value.to_value() {
+            Ok(root) => self.select(&root).map(Cow::into_owned).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
 }
 
 impl FromStr for JsonPathInst {
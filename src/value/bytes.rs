@@ -1,10 +1,53 @@
-use crate::{bytestring::ByteString, map::Map, value::Value};
-use bytes::Bytes;
+use crate::bytestring::{self, ByteString};
+use crate::value::encode;
+use crate::value::input::Input;
+use crate::value::stream;
+use crate::value::{DuplicateKeys, LimitExceeded, ParseOptions};
+use crate::{map::Map, value::Value, KeyInterner};
+use bytes::{Bytes, BytesMut};
 use serde::de::SeqAccess;
-use serde::de::{DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, Visitor};
 use serde_json::Number;
+
+#[cfg(feature = "arbitrary_precision")]
+use crate::number::NumberFromString;
+use std::cell::Cell;
 use std::fmt;
 
+/// Deserializes an instance of `T` from the JSON document in `data`.
+///
+/// The [`ByteString`], [`Value`] and [`Map`] fields of `T` reference `data`
+/// like the strings of [`Value::from_bytes`], instead of copying them. Strings
+/// with escape sequences are still copied.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde::Deserialize;
+/// # use serde_json_bytes::{ByteString, Value};
+/// #[derive(Deserialize)]
+/// struct Request {
+///     query: ByteString,
+///     variables: Value,
+/// }
+///
+/// let body = Bytes::from_static(br#"{"query": "{ me { name } }", "variables": {"id": "1"}}"#);
+/// let request: Request = serde_json_bytes::from_bytes(body.clone())?;
+///
+/// assert_eq!(request.query, "{ me { name } }");
+/// assert!(body.as_ptr_range().contains(&request.query.as_ptr()));
+/// assert_eq!(request.variables["id"], "1");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn from_bytes<T>(data: Bytes) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    let mut de = serde_json::Deserializer::from_slice(&data);
+    let value = tri!(T::deserialize(Input::new(&mut de, &data)));
+    tri!(de.end());
+    Ok(value)
+}
+
 impl Value {
     pub fn from_bytes(data: Bytes) -> Result<Value, serde_json::Error> {
         let seed = BytesSeed::new(&data);
@@ -12,22 +55,159 @@ impl Value {
         seed.deserialize(&mut de)
     }
 
+    /// Parses a document held in a `String`, like [`Value::from_bytes`].
+    ///
+    /// The `String` becomes the buffer the strings of the value reference,
+    /// instead of each of them being copied.
+    ///
+    /// ```
+    /// # use serde_json_bytes::Value;
+    /// let input = String::from(r#"{"query": "{ me { name } }"}"#);
+    /// let start = input.as_ptr();
+    /// let value = Value::from_string(input)?;
+    ///
+    /// assert_eq!(value["query"], "{ me { name } }");
+    /// assert_eq!(value["query"].as_str().unwrap().as_ptr(), start.wrapping_add(11));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn from_string(data: String) -> Result<Value, serde_json::Error> {
+        Value::from_bytes(data.into())
+    }
+
+    /// Parses a document held in a `Vec<u8>`, taking ownership of it like
+    /// [`Value::from_string`].
+    pub fn from_vec(data: Vec<u8>) -> Result<Value, serde_json::Error> {
+        Value::from_bytes(data.into())
+    }
+
+    /// Parses a document held in a `BytesMut`, taking ownership of it like
+    /// [`Value::from_string`].
+    pub fn from_bytes_mut(data: BytesMut) -> Result<Value, serde_json::Error> {
+        Value::from_bytes(data.freeze())
+    }
+
+    /// Parses a static document. Its strings reference it directly, without
+    /// any allocation.
+    pub fn from_static(data: &'static str) -> Result<Value, serde_json::Error> {
+        Value::from_bytes(Bytes::from_static(data.as_bytes()))
+    }
+
+    /// Serializes this value as compact JSON.
+    ///
+    /// The exact encoded length is computed first, so the output is written
+    /// in a single allocation. Strings parsed without escape sequences are
+    /// copied without being scanned again, unlike with the serializers of
+    /// other crates, which scan every string they get through `Serialize`.
     pub fn to_bytes(&self) -> Bytes {
-        self.to_string().into()
+        let mut buf = BytesMut::with_capacity(encode::value_len(self));
+        encode::write_value(self, &mut buf);
+        buf.freeze()
+    }
+
+    /// Serializes this value as compact JSON at the end of `buf`.
+    pub fn to_bytes_into(&self, buf: &mut BytesMut) {
+        buf.reserve(encode::value_len(self));
+        encode::write_value(self, buf);
     }
 }
 
 /// The [`DeserializeSeed`] implementation that returns [`Value`].
 ///
 /// Can be used in `DeserializeSeed` implementations for types that contain `Value`.
+///
+/// `bytes` must be the input of the `serde_json` deserializer. The strings it
+/// lends are slices of JSON string literals without escape sequences, so they
+/// are known not to need escaping when serialized with [`Value::to_bytes`].
+///
+/// serde_json decodes strings with escape sequences before handing them to
+/// the seed, which then has to copy them. To keep them as slices of the input,
+/// parse with [`ParseOptions::defer_unescape`](crate::ParseOptions::defer_unescape)
+/// instead.
 #[derive(Clone, Copy)]
 pub struct BytesSeed<'data> {
     bytes: &'data Bytes,
+    interner: Option<&'data KeyInterner>,
+    options: Option<&'data ParseOptions>,
 }
 
 impl<'data> BytesSeed<'data> {
     pub const fn new(bytes: &'data Bytes) -> Self {
-        BytesSeed { bytes }
+        BytesSeed {
+            bytes,
+            interner: None,
+            options: None,
+        }
+    }
+
+    /// Takes the object keys from `interner`, so that each distinct key is
+    /// stored once instead of referencing the input at every occurrence.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde::de::DeserializeSeed;
+    /// # use serde_json_bytes::{value::BytesSeed, KeyInterner};
+    /// let data = Bytes::from_static(br#"[{"id": 1}, {"id": 2}, {"i\u0064": 3}]"#);
+    /// let interner = KeyInterner::new();
+    /// let seed = BytesSeed::with_interner(&data, &interner);
+    /// let value = seed.deserialize(&mut serde_json::Deserializer::from_slice(&data))?;
+    ///
+    /// assert_eq!(value[2]["id"], 3);
+    /// assert_eq!(interner.len(), 1);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub const fn with_interner(bytes: &'data Bytes, interner: &'data KeyInterner) -> Self {
+        BytesSeed {
+            bytes,
+            interner: Some(interner),
+            options: None,
+        }
+    }
+
+    /// Applies the limits, the duplicate key policy and the key interner of
+    /// `options`. The other options concern the crate's own parser, and are
+    /// ignored.
+    ///
+    /// The input size is checked before deserializing, the other limits
+    /// before building the value going over them. Strings with escape
+    /// sequences are decoded by serde_json, so their decoded length is
+    /// checked. Nesting depths above 128 also need serde_json's recursion
+    /// limit to be disabled.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde::de::DeserializeSeed;
+    /// # use serde_json_bytes::{value::BytesSeed, LimitExceeded, ParseOptions};
+    /// let data = Bytes::from_static(br#"{"a": [1, 2, 3]}"#);
+    /// let options = ParseOptions::new().max_array_length(2);
+    /// let seed = BytesSeed::with_options(&data, &options);
+    /// let err = seed
+    ///     .deserialize(&mut serde_json::Deserializer::from_slice(&data))
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(LimitExceeded::from_error(&err), Some(LimitExceeded::ArrayLength(2)));
+    /// ```
+    pub fn with_options(bytes: &'data Bytes, options: &'data ParseOptions) -> Self {
+        BytesSeed {
+            bytes,
+            interner: options.key_interner.as_deref(),
+            options: Some(options),
+        }
+    }
+
+    fn option(self, option: impl FnOnce(&ParseOptions) -> Option<usize>) -> Option<usize> {
+        self.options.and_then(option)
+    }
+
+    /// Checks the input size and returns the seed of the document, whose
+    /// values are counted in `nodes`. Used both as a seed and as a visitor,
+    /// so the limits hold either way.
+    fn start<E: de::Error>(self, nodes: &Cell<usize>) -> Result<Seed<'_, 'data>, E> {
+        if let Some(max) = self.option(|options| options.max_size) {
+            if self.bytes.len() > max {
+                return Err(E::custom(LimitExceeded::Size(max)));
+            }
+        }
+        Ok(Seed::new(self, nodes))
     }
 }
 
@@ -38,7 +218,8 @@ impl<'de, 'data> DeserializeSeed<'de> for BytesSeed<'data> {
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(self)
+        let nodes = Cell::new(0);
+        deserializer.deserialize_any(tri!(self.start(&nodes)))
     }
 }
 
@@ -50,51 +231,246 @@ impl<'de, 'data> Visitor<'de> for BytesSeed<'data> {
     }
 
     #[inline]
-    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_bool(value)
+    }
+
+    #[inline]
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_i64(value)
+    }
+
+    #[inline]
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_u64(value)
+    }
+
+    #[inline]
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_f64(value)
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_str(value)
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_borrowed_str(value)
+    }
+
+    #[inline]
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_string(value)
+    }
+
+    #[inline]
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_none()
+    }
+
+    #[inline]
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        tri!(self.start(&Cell::new(0))).visit_unit()
+    }
+
+    #[inline]
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        DeserializeSeed::deserialize(self, deserializer)
+    }
+
+    #[inline]
+    fn visit_seq<V>(self, visitor: V) -> Result<Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        tri!(self.start(&Cell::new(0))).visit_seq(visitor)
+    }
+
+    fn visit_map<V>(self, visitor: V) -> Result<Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        tri!(self.start(&Cell::new(0))).visit_map(visitor)
+    }
+}
+
+/// The state of a [`BytesSeed`] while deserializing a value: the depth and
+/// path of the value, and the number of values deserialized so far.
+#[derive(Clone, Copy)]
+struct Seed<'a, 'data> {
+    seed: BytesSeed<'data>,
+    depth: usize,
+    path: &'a Path<'a>,
+    nodes: &'a Cell<usize>,
+}
+
+/// Location of a value in the document, to report duplicate keys.
+enum Path<'a> {
+    Root,
+    Index(&'a Path<'a>, usize),
+    Key(&'a Path<'a>, &'a str),
+}
+
+impl Path<'_> {
+    /// Appends the JSON Pointer of this location to `pointer`.
+    fn push_to(&self, pointer: &mut String) {
+        match self {
+            Path::Root => {}
+            Path::Index(parent, index) => {
+                parent.push_to(pointer);
+                stream::push_token(pointer, &index.to_string());
+            }
+            Path::Key(parent, key) => {
+                parent.push_to(pointer);
+                stream::push_token(pointer, key);
+            }
+        }
+    }
+}
+
+impl<'a, 'data> Seed<'a, 'data> {
+    fn new(seed: BytesSeed<'data>, nodes: &'a Cell<usize>) -> Self {
+        Seed {
+            seed,
+            depth: 0,
+            path: &Path::Root,
+            nodes,
+        }
+    }
+
+    /// Returns this seed for the value at `path`.
+    fn at<'b>(self, path: &'b Path<'b>) -> Seed<'b, 'data>
+    where
+        'a: 'b,
+    {
+        Seed { path, ..self }
+    }
+
+    /// Checks the limit on the number of values, for a new one.
+    fn count<E: de::Error>(self) -> Result<(), E> {
+        self.nodes.set(self.nodes.get() + 1);
+        match self.seed.option(|options| options.max_nodes) {
+            Some(max) if self.nodes.get() > max => Err(E::custom(LimitExceeded::Nodes(max))),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_length<E: de::Error>(self, value: &str) -> Result<(), E> {
+        match self.seed.option(|options| options.max_string_length) {
+            Some(max) if value.len() > max => Err(E::custom(LimitExceeded::StringLength(max))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the seed of the values of an array or object.
+    fn nested<E: de::Error>(self) -> Result<Self, E> {
+        match self.seed.option(|options| options.max_depth) {
+            Some(max) if self.depth >= max => Err(E::custom(LimitExceeded::Depth(max))),
+            _ => Ok(Seed {
+                depth: self.depth + 1,
+                ..self
+            }),
+        }
+    }
+
+    fn key_seed(self) -> ByteStringSeed<'data> {
+        ByteStringSeed {
+            bytes: self.seed.bytes,
+            interner: self.seed.interner,
+            max_length: self.seed.option(|options| options.max_string_length),
+        }
+    }
+}
+
+impl<'de, 'a, 'data> DeserializeSeed<'de> for Seed<'a, 'data> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a, 'data> Visitor<'de> for Seed<'a, 'data> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    #[inline]
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Value::Bool(value))
     }
 
     #[inline]
-    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Value::Number(value.into()))
     }
 
     #[inline]
-    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Value::Number(value.into()))
     }
 
     #[inline]
-    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
     }
 
     #[inline]
     fn visit_str<E>(self, value: &str) -> Result<Value, E>
     where
-        E: serde::de::Error,
+        E: de::Error,
     {
+        tri!(self.count());
+        tri!(self.check_length(value));
         Ok(Value::String(value.into()))
     }
 
     #[inline]
     fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Self::Value, E>
     where
-        E: serde::de::Error,
+        E: de::Error,
     {
-        Ok(Value::String(ByteString::new(self.bytes, value)))
+        tri!(self.count());
+        tri!(self.check_length(value));
+        // serde_json lends some strings that are not part of the input, like
+        // the token of arbitrary precision numbers
+        if bytestring::contains(self.seed.bytes, value) {
+            Ok(Value::String(ByteString::new_escape_free(
+                self.seed.bytes,
+                value,
+            )))
+        } else {
+            Ok(Value::String(value.into()))
+        }
     }
 
     #[inline]
     fn visit_string<E>(self, value: String) -> Result<Value, E>
     where
-        E: serde::de::Error,
+        E: de::Error,
     {
+        tri!(self.count());
+        tri!(self.check_length(&value));
         Ok(Value::String(value.into()))
     }
 
     #[inline]
-    fn visit_none<E>(self) -> Result<Value, E> {
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Value::Null)
     }
 
@@ -107,7 +483,8 @@ impl<'de, 'data> Visitor<'de> for BytesSeed<'data> {
     }
 
     #[inline]
-    fn visit_unit<E>(self) -> Result<Value, E> {
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        tri!(self.count());
         Ok(Value::Null)
     }
 
@@ -116,10 +493,22 @@ impl<'de, 'data> Visitor<'de> for BytesSeed<'data> {
     where
         V: SeqAccess<'de>,
     {
+        tri!(self.count());
+        let seed = tri!(self.nested());
+        let max = self.seed.option(|options| options.max_array_length);
         let mut vec = Vec::new();
 
-        while let Some(elem) = visitor.next_element_seed(self)? {
-            vec.push(elem);
+        loop {
+            if Some(vec.len()) == max {
+                // fails on the next element, before deserializing it
+                tri!(visitor.next_element_seed(Exceeded(LimitExceeded::ArrayLength(vec.len()))));
+                break;
+            }
+            let path = Path::Index(self.path, vec.len());
+            match tri!(visitor.next_element_seed(seed.at(&path))) {
+                Some(elem) => vec.push(elem),
+                None => break,
+            }
         }
 
         Ok(Value::Array(vec))
@@ -129,15 +518,46 @@ impl<'de, 'data> Visitor<'de> for BytesSeed<'data> {
     where
         V: MapAccess<'de>,
     {
-        match visitor.next_key_seed(ByteStringSeed::new(self.bytes))? {
+        tri!(self.count());
+        match tri!(visitor.next_key_seed(self.key_seed())) {
+            #[cfg(feature = "arbitrary_precision")]
+            Some(first_key) if first_key == crate::number::TOKEN => {
+                let number: NumberFromString = tri!(visitor.next_value());
+                Ok(Value::Number(number.value))
+            }
             Some(first_key) => {
+                let seed = tri!(self.nested());
+                let max = self.seed.option(|options| options.max_object_keys);
+                let policy = self
+                    .seed
+                    .options
+                    .map_or(DuplicateKeys::KeepLast, |options| options.duplicate_keys);
                 let mut values = Map::new();
 
-                values.insert(first_key, tri!(visitor.next_value_seed(self)));
-                while let Some((key, value)) =
-                    tri!(visitor.next_entry_seed(ByteStringSeed::new(self.bytes), self))
-                {
-                    values.insert(key, value);
+                let mut entries = 0;
+                let mut next = Some(first_key);
+                while let Some(key) = next {
+                    if Some(entries) == max {
+                        return Err(de::Error::custom(LimitExceeded::ObjectKeys(entries)));
+                    }
+                    entries += 1;
+                    let duplicate =
+                        policy != DuplicateKeys::KeepLast && values.contains_key(key.as_str());
+                    if duplicate && policy == DuplicateKeys::Reject {
+                        let mut pointer = String::new();
+                        self.path.push_to(&mut pointer);
+                        return Err(de::Error::custom(stream::duplicate_key_error(
+                            &key, &pointer,
+                        )));
+                    }
+                    let path = Path::Key(self.path, &key);
+                    let value = tri!(visitor.next_value_seed(seed.at(&path)));
+                    // the value of a duplicate key is still checked against
+                    // the limits, like with the crate's parser
+                    if !duplicate {
+                        values.insert(key, value);
+                    }
+                    next = tri!(visitor.next_key_seed(self.key_seed()));
                 }
 
                 Ok(Value::Object(values))
@@ -147,17 +567,60 @@ impl<'de, 'data> Visitor<'de> for BytesSeed<'data> {
     }
 }
 
+/// Fails with the error of a limit if there is another element to
+/// deserialize.
+struct Exceeded(LimitExceeded);
+
+impl<'de> DeserializeSeed<'de> for Exceeded {
+    type Value = ();
+
+    fn deserialize<D>(self, _deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Err(de::Error::custom(self.0))
+    }
+}
+
 /// A [`DeserializeSeed`] implementation that returns [`ByteString`].
 ///
 /// Can be used in `DeserializeSeed` implementations for types that contain `ByteString`.
+///
+/// Like with [`BytesSeed`], `bytes` must be the input of the `serde_json`
+/// deserializer, and strings with escape sequences are copied.
 #[derive(Clone, Copy)]
 pub struct ByteStringSeed<'data> {
     bytes: &'data Bytes,
+    interner: Option<&'data KeyInterner>,
+    max_length: Option<usize>,
 }
 
 impl<'data> ByteStringSeed<'data> {
     pub const fn new(bytes: &'data Bytes) -> Self {
-        ByteStringSeed { bytes }
+        ByteStringSeed {
+            bytes,
+            interner: None,
+            max_length: None,
+        }
+    }
+
+    /// Returns the strings from `interner`, for object keys or other strings
+    /// that repeat.
+    pub const fn with_interner(bytes: &'data Bytes, interner: &'data KeyInterner) -> Self {
+        ByteStringSeed {
+            bytes,
+            interner: Some(interner),
+            max_length: None,
+        }
+    }
+}
+
+impl<'data> ByteStringSeed<'data> {
+    fn check_length<E: de::Error>(self, value: &str) -> Result<(), E> {
+        match self.max_length {
+            Some(max) if value.len() > max => Err(E::custom(LimitExceeded::StringLength(max))),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -184,7 +647,11 @@ impl<'de, 'data> Visitor<'de> for ByteStringSeed<'data> {
     where
         E: serde::de::Error,
     {
-        Ok(value.into())
+        tri!(self.check_length(value));
+        match self.interner {
+            Some(interner) => Ok(interner.intern(value)),
+            None => Ok(value.into()),
+        }
     }
 
     #[inline]
@@ -192,7 +659,14 @@ impl<'de, 'data> Visitor<'de> for ByteStringSeed<'data> {
     where
         E: serde::de::Error,
     {
-        Ok(ByteString::new(self.bytes, value))
+        tri!(self.check_length(value));
+        match self.interner {
+            Some(interner) => Ok(interner.intern(value)),
+            None if bytestring::contains(self.bytes, value) => {
+                Ok(ByteString::new_escape_free(self.bytes, value))
+            }
+            None => Ok(value.into()),
+        }
     }
 
     #[inline]
@@ -200,6 +674,227 @@ impl<'de, 'data> Visitor<'de> for ByteStringSeed<'data> {
     where
         E: serde::de::Error,
     {
-        Ok(value.into())
+        tri!(self.check_length(&value));
+        match self.interner {
+            Some(interner) => Ok(interner.intern(&value)),
+            None => Ok(value.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::BytesSeed;
+    use crate::{json, ByteString, DuplicateKeys, LimitExceeded, Map, ParseOptions, Value};
+    use bytes::{Bytes, BytesMut};
+    use serde::de::DeserializeSeed;
+    use serde::{Deserialize, Deserializer};
+
+    #[test]
+    fn owned_inputs() {
+        const INPUT: &str = r#"{"a": ["a string long enough not to be inline", "\n"]}"#;
+        let expected = json!({"a": ["a string long enough not to be inline", "\n"]});
+
+        let value = Value::from_static(INPUT).unwrap();
+        assert_eq!(value, expected);
+        let s = value["a"][0].as_str().unwrap();
+        assert!(INPUT.as_bytes().as_ptr_range().contains(&s.as_ptr()));
+
+        let vec = INPUT.as_bytes().to_vec();
+        let range = vec.as_ptr_range();
+        let value = Value::from_vec(vec).unwrap();
+        assert_eq!(value, expected);
+        assert!(range.contains(&value["a"][0].as_str().unwrap().as_ptr()));
+
+        let buf = BytesMut::from(INPUT);
+        let range = buf.as_ptr_range();
+        let value = Value::from_bytes_mut(buf).unwrap();
+        assert_eq!(value, expected);
+        assert!(range.contains(&value["a"][0].as_str().unwrap().as_ptr()));
+
+        assert!(Value::from_vec(b"[\"\xFF\"]".to_vec()).is_err());
+        assert!(Value::from_string("{".to_owned()).is_err());
+    }
+
+    #[test]
+    fn typed() {
+        #[derive(Deserialize)]
+        struct Request {
+            query: ByteString,
+            name: Option<ByteString>,
+            variables: Map<ByteString, Value>,
+            extensions: Value,
+            other: String,
+        }
+
+        let body = Bytes::from_static(
+            br#"{"query": "q", "name": "op\u0065", "variables": {"k": ["v"]},
+            "extensions": {"e": {"f": "g"}}, "other": "o"}"#,
+        );
+        let request: Request = crate::from_bytes(body.clone()).unwrap();
+        let in_body = |s: &str| body.as_ptr_range().contains(&s.as_ptr());
+
+        assert_eq!(request.query, "q");
+        assert!(in_body(&request.query));
+        assert_eq!(request.name.unwrap(), "ope");
+        let (key, value) = request.variables.iter().next().unwrap();
+        assert!(in_body(key) && in_body(value[0].as_str().unwrap()));
+        assert_eq!(request.extensions, json!({"e": {"f": "g"}}));
+        let (key, value) = request
+            .extensions
+            .as_object()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap();
+        assert!(in_body(key) && in_body(value["f"].as_str().unwrap()));
+        assert_eq!(request.other, "o");
+
+        #[derive(Deserialize)]
+        enum Operation {
+            Query(ByteString),
+            Batch(Vec<Value>),
+        }
+
+        let body = Bytes::from_static(br#"[{"Query": "q"}, {"Batch": [{"a": "b"}]}]"#);
+        let operations: Vec<Operation> = crate::from_bytes(body.clone()).unwrap();
+        let in_body = |s: &str| body.as_ptr_range().contains(&s.as_ptr());
+        match &operations[..] {
+            [Operation::Query(query), Operation::Batch(batch)] => {
+                assert!(in_body(query));
+                assert!(in_body(batch[0]["a"].as_str().unwrap()));
+            }
+            _ => panic!("unexpected operations"),
+        }
+
+        // strings from another buffer are copied
+        let outside: ByteString = serde_json::from_str(r#""s""#).unwrap();
+        assert_eq!(outside, "s");
+        assert!(crate::from_bytes::<Request>(Bytes::from_static(b"{} 1")).is_err());
+    }
+
+    #[test]
+    fn limits() {
+        let parse = |input: &'static str, options: ParseOptions| {
+            let bytes = Bytes::from_static(input.as_bytes());
+            let mut de = serde_json::Deserializer::from_slice(&bytes);
+            BytesSeed::with_options(&bytes, &options)
+                .deserialize(&mut de)
+                .map_err(|err| (LimitExceeded::from_error(&err), err.to_string()))
+        };
+        let limit = |input, options| parse(input, options).unwrap_err().0;
+
+        assert!(parse("[[1]]", ParseOptions::new().max_depth(2)).is_ok());
+        assert_eq!(
+            limit("[[[1]]]", ParseOptions::new().max_depth(2)),
+            Some(LimitExceeded::Depth(2))
+        );
+
+        let options = ParseOptions::new().max_string_length(3);
+        assert!(parse(r#"{"abc": "d\n"}"#, options.clone()).is_ok());
+        assert_eq!(
+            limit(r#"{"abcd": 1}"#, options.clone()),
+            Some(LimitExceeded::StringLength(3))
+        );
+        assert_eq!(
+            limit(r#"["abcd"]"#, options),
+            Some(LimitExceeded::StringLength(3))
+        );
+
+        let options = ParseOptions::new().max_object_keys(2);
+        assert!(parse(r#"{"a": 1, "b": [2]}"#, options.clone()).is_ok());
+        assert_eq!(
+            parse(r#"{"a": 1, "b": 2, "c": 3}"#, options).unwrap_err(),
+            (
+                Some(LimitExceeded::ObjectKeys(2)),
+                "maximum number of object keys of 2 exceeded at line 1 column 20".to_owned()
+            )
+        );
+
+        let options = ParseOptions::new().max_array_length(2);
+        assert!(parse("[1, [2, 3]]", options.clone()).is_ok());
+        assert_eq!(
+            parse("[1, 2, 3]", options.clone()).unwrap_err(),
+            (
+                Some(LimitExceeded::ArrayLength(2)),
+                "maximum array length of 2 exceeded at line 1 column 7".to_owned()
+            )
+        );
+        assert_eq!(
+            limit("[[]]", ParseOptions::new().max_array_length(0)),
+            Some(LimitExceeded::ArrayLength(0))
+        );
+
+        // the array, the object, "a" and "b"
+        assert!(parse(r#"[{"k": "a"}, "b"]"#, ParseOptions::new().max_nodes(4)).is_ok());
+        assert_eq!(
+            limit(
+                r#"[{"k": "a"}, "b", null]"#,
+                ParseOptions::new().max_nodes(4)
+            ),
+            Some(LimitExceeded::Nodes(4))
+        );
+
+        assert!(parse("[1, 2]", ParseOptions::new().max_size(6)).is_ok());
+        assert_eq!(
+            parse("[1, 2] ", ParseOptions::new().max_size(6)).unwrap_err(),
+            (
+                Some(LimitExceeded::Size(6)),
+                "maximum input size of 6 bytes exceeded".to_owned()
+            )
+        );
+
+        // the same limits apply to the seed used as a visitor
+        let visit = |input: &'static str, options: ParseOptions| {
+            let bytes = Bytes::from_static(input.as_bytes());
+            let mut de = serde_json::Deserializer::from_slice(&bytes);
+            let err = de
+                .deserialize_any(BytesSeed::with_options(&bytes, &options))
+                .unwrap_err();
+            LimitExceeded::from_error(&err)
+        };
+        assert_eq!(
+            visit("[[1], [2]]", ParseOptions::new().max_nodes(4)),
+            Some(LimitExceeded::Nodes(4))
+        );
+        assert_eq!(
+            visit("[1, 2] ", ParseOptions::new().max_size(6)),
+            Some(LimitExceeded::Size(6))
+        );
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let parse = |input: &'static str, policy| {
+            let bytes = Bytes::from_static(input.as_bytes());
+            let options = ParseOptions::new().duplicate_keys(policy);
+            let mut de = serde_json::Deserializer::from_slice(&bytes);
+            BytesSeed::with_options(&bytes, &options)
+                .deserialize(&mut de)
+                .map_err(|e| e.to_string())
+        };
+        let input = r#"{"a": 1, "b": {"c": 2}, "a": [3], "b": {"d": 4}}"#;
+
+        assert_eq!(
+            parse(input, DuplicateKeys::KeepFirst).unwrap(),
+            json!({"a": 1, "b": {"c": 2}})
+        );
+        assert_eq!(
+            parse(input, DuplicateKeys::KeepLast).unwrap(),
+            json!({"a": [3], "b": {"d": 4}})
+        );
+        assert_eq!(
+            parse(input, DuplicateKeys::Reject).unwrap_err(),
+            "duplicate key \"a\" in the root object at line 1 column 27"
+        );
+        assert_eq!(
+            parse(
+                r#"{"x": [0, {"a/b": {"k": 1, "k": 2}}]}"#,
+                DuplicateKeys::Reject
+            )
+            .unwrap_err(),
+            "duplicate key \"k\" in the object at /x/1/a~1b at line 1 column 30"
+        );
+        assert!(parse(r#"[{"a": 1}, {"a": 2}]"#, DuplicateKeys::Reject).is_ok());
     }
 }
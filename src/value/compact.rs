@@ -0,0 +1,377 @@
+use crate::lib::*;
+use crate::value::{RawBytesValue, Value};
+use crate::{ByteString, Map};
+use bytes::{Bytes, BytesMut};
+
+/// Memory kept alive by the strings and raw values of a value, as reported
+/// by [`Value::buffer_usage`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferUsage {
+    /// Number of distinct buffers referenced.
+    pub buffers: usize,
+    /// Total length of those buffers.
+    pub size: usize,
+    /// Number of bytes of those buffers actually referenced. Bytes referenced
+    /// several times are counted once.
+    pub referenced: usize,
+}
+
+impl Value {
+    /// Copies the strings and raw values that keep a mostly unused buffer
+    /// alive into a single buffer of the exact size needed, so that the
+    /// buffers they were parsed from can be freed.
+    ///
+    /// A string parsed from a `Bytes` buffer references it, so keeping a
+    /// small part of a large document keeps the whole document in memory.
+    /// Only the strings referencing buffers of which this value uses less
+    /// than half are copied: static strings, strings owning their buffer and
+    /// strings sharing a buffer mostly used by this value are left as is.
+    /// Use [`Value::buffer_usage`] to find out how much memory is kept alive.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::Value;
+    /// let response = Bytes::from(format!(r#"{{"id": "a", "padding": "{}"}}"#, "x".repeat(1 << 20)));
+    /// let mut id = Value::from_bytes(response.clone())?["id"].clone();
+    /// assert_eq!(id.buffer_usage().size, response.len());
+    ///
+    /// id.compact();
+    /// assert_eq!(id.buffer_usage().size, 1);
+    /// assert_eq!(id, "a");
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn compact(&mut self) {
+        let buffers = Buffers::new(self);
+        let mut len = 0;
+        self.for_each_buffer(&mut |bytes, buffer_len| {
+            if buffers.is_wasted(bytes, buffer_len) {
+                len += bytes.len();
+            }
+        });
+        if len == 0 {
+            return;
+        }
+        let mut compaction = Compaction {
+            buffers,
+            buf: BytesMut::with_capacity(len),
+            len,
+        };
+        compaction.value(self);
+        debug_assert_eq!(
+            compaction.buf.capacity(),
+            0,
+            "compacted buffer was not tightly sized"
+        );
+    }
+
+    /// Returns how many distinct buffers the strings and raw values of this
+    /// value keep alive, their total size, and how much of them is used.
+    ///
+    /// Strings and raw values record the length of the input they were parsed
+    /// from, or their own length if they were copied into a buffer of their
+    /// own. Strings and raw values created from a `Bytes` slice, for example
+    /// with `ByteString::try_from`, count as buffers of their own length.
+    /// Static strings and strings stored inline do not keep any buffer alive.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, Value};
+    /// let response = Bytes::from_static(br#"{"a": "a string longer than the inline capacity", "b": 1}"#);
+    /// let value = json!({"a": Value::from_bytes(response.clone())?["a"].clone(), "b": "b"});
+    ///
+    /// let usage = value.buffer_usage();
+    /// assert_eq!(usage.buffers, 1);
+    /// assert_eq!(usage.size, response.len());
+    /// assert_eq!(usage.referenced, 40);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn buffer_usage(&self) -> BufferUsage {
+        let mut usage = BufferUsage::default();
+        for buffer in &Buffers::new(self).0 {
+            usage.buffers += 1;
+            usage.size += buffer.len;
+            usage.referenced += buffer.referenced;
+        }
+        usage
+    }
+
+    /// Returns how many bytes of `source` are referenced by the strings and
+    /// raw values of this value. Bytes referenced several times are counted
+    /// once.
+    ///
+    /// As long as this is not zero, this value keeps the whole `source`
+    /// buffer alive. [`Value::compact`] removes those references.
+    pub fn referenced_bytes(&self, source: &Bytes) -> usize {
+        let source = source.as_ptr_range();
+        let mut ranges = Vec::new();
+        self.for_each_buffer(&mut |bytes, _| {
+            let range = bytes.as_ptr_range();
+            if !bytes.is_empty() && source.start <= range.start && range.end <= source.end {
+                ranges.push((range.start as usize, range.end as usize));
+            }
+        });
+
+        ranges.sort_unstable();
+        let mut total = 0;
+        let mut covered = 0;
+        for (start, end) in ranges {
+            let start = start.max(covered);
+            if end > start {
+                total += end - start;
+                covered = end;
+            }
+        }
+        total
+    }
+
+    /// Calls `f` with every buffer slice referenced by this value, and the
+    /// length of the buffer it is a slice of.
+    fn for_each_buffer(&self, f: &mut impl FnMut(&Bytes, usize)) {
+        match self {
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+            Value::String(s) => {
+                if let Some((stored, len)) = s.stored() {
+                    f(stored, len);
+                }
+            }
+            Value::Array(v) => {
+                for elem in v {
+                    elem.for_each_buffer(f);
+                }
+            }
+            Value::Object(m) => {
+                for (k, v) in m {
+                    if let Some((stored, len)) = k.stored() {
+                        f(stored, len);
+                    }
+                    v.for_each_buffer(f);
+                }
+            }
+            Value::Raw(raw) => {
+                let (stored, len) = raw.stored();
+                f(stored, len);
+            }
+        }
+    }
+}
+
+/// The buffers referenced by a value, sorted by length and address.
+struct Buffers(Vec<Buffer>);
+
+struct Buffer {
+    len: usize,
+    /// Addresses of the first and last referenced bytes.
+    start: usize,
+    end: usize,
+    referenced: usize,
+}
+
+impl Buffers {
+    fn new(value: &Value) -> Self {
+        let mut slices = Vec::new();
+        value.for_each_buffer(&mut |bytes, len| {
+            // static strings do not keep any memory alive
+            if len > 0 && !bytes.is_empty() {
+                let range = bytes.as_ptr_range();
+                slices.push((len, range.start as usize, range.end as usize));
+            }
+        });
+        slices.sort_unstable();
+
+        // `Bytes` does not tell which allocation a slice belongs to: slices of
+        // buffers of the same length belong to the same one if they fit in it
+        // together
+        let mut buffers: Vec<Buffer> = Vec::new();
+        for (len, start, end) in slices {
+            match buffers.last_mut() {
+                Some(buffer) if buffer.len == len && end - buffer.start <= len => {
+                    buffer.referenced += end.saturating_sub(start.max(buffer.end));
+                    buffer.end = buffer.end.max(end);
+                }
+                _ => buffers.push(Buffer {
+                    len,
+                    start,
+                    end,
+                    referenced: end - start,
+                }),
+            }
+        }
+        Buffers(buffers)
+    }
+
+    /// Returns whether `bytes`, a slice of a buffer of `len` bytes, keeps a
+    /// buffer alive of which less than half is referenced.
+    fn is_wasted(&self, bytes: &Bytes, len: usize) -> bool {
+        if len == 0 || bytes.is_empty() {
+            return false;
+        }
+        let start = bytes.as_ptr() as usize;
+        let i = self
+            .0
+            .partition_point(|buffer| (buffer.len, buffer.start) <= (len, start));
+        match i.checked_sub(1).map(|i| &self.0[i]) {
+            Some(buffer) if buffer.len == len && start < buffer.end => {
+                buffer.referenced < buffer.len / 2
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Copies the strings and raw values that keep a mostly unused buffer alive
+/// into `buf`, of exactly `len` bytes.
+struct Compaction {
+    buffers: Buffers,
+    buf: BytesMut,
+    len: usize,
+}
+
+impl Compaction {
+    fn value(&mut self, value: &mut Value) {
+        match value {
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+            Value::String(s) => {
+                if let Some(string) = self.string(s) {
+                    *s = string;
+                }
+            }
+            Value::Array(v) => {
+                for elem in v {
+                    self.value(elem);
+                }
+            }
+            Value::Object(m) => {
+                let wasted = |k: &ByteString| matches!(k.stored(), Some((stored, len)) if self.buffers.is_wasted(stored, len));
+                if m.keys().any(wasted) {
+                    // keys cannot be modified in place
+                    *m = mem::take(m)
+                        .into_iter()
+                        .map(|(k, mut v)| {
+                            let k = self.string(&k).unwrap_or(k);
+                            self.value(&mut v);
+                            (k, v)
+                        })
+                        .collect::<Map<ByteString, Value>>();
+                } else {
+                    for v in m.values_mut() {
+                        self.value(v);
+                    }
+                }
+            }
+            Value::Raw(raw) => {
+                let (stored, len) = raw.stored();
+                if self.buffers.is_wasted(stored, len) {
+                    let copy = self.copy(stored);
+                    *raw = RawBytesValue::from_validated(copy).within(self.len);
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of `s` if it keeps a mostly unused buffer alive.
+    fn string(&mut self, s: &ByteString) -> Option<ByteString> {
+        match s.stored() {
+            Some((stored, len)) if self.buffers.is_wasted(stored, len) => {
+                let copy = self.copy(stored);
+                Some(s.with_stored(copy).within(self.len))
+            }
+            _ => None,
+        }
+    }
+
+    /// Appends `bytes` to the buffer and splits them off, without
+    /// reallocating as long as it has enough capacity left.
+    fn copy(&mut self, bytes: &Bytes) -> Bytes {
+        self.buf.extend_from_slice(bytes);
+        self.buf.split().freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{BufferUsage, RawBytesValue};
+    use crate::{json, ByteString, ParseOptions, Value};
+    use bytes::Bytes;
+
+    #[test]
+    fn compact() {
+        let source = Bytes::from_static(
+            br#"{"keep": {"a": "b", "escaped": "\n", "list": ["c", "d"]}, "drop": "eeeeeeeeee"}"#,
+        );
+        let options = ParseOptions::new().defer_unescape(true);
+        let value = Value::from_bytes_with_options(source.clone(), &options).unwrap();
+        assert_eq!(value.referenced_bytes(&source), 35);
+
+        let mut keep = value["keep"].clone();
+        // "a", "b", "escaped", "\n", "list", "c" and "d"
+        assert_eq!(keep.referenced_bytes(&source), 17);
+        assert_eq!(keep.referenced_bytes(&Bytes::from_static(b"other")), 0);
+        let usage = BufferUsage {
+            buffers: 1,
+            size: source.len(),
+            referenced: 17,
+        };
+        assert_eq!(keep.buffer_usage(), usage);
+
+        keep.compact();
+        assert_eq!(keep.referenced_bytes(&source), 0);
+        let usage = BufferUsage {
+            buffers: 1,
+            size: 17,
+            referenced: 17,
+        };
+        assert_eq!(keep.buffer_usage(), usage);
+        assert_eq!(keep, value["keep"]);
+        assert_eq!(keep.to_bytes(), value["keep"].to_bytes());
+
+        // the copy is used up
+        keep.compact();
+        assert_eq!(keep.buffer_usage(), usage);
+
+        let text = Bytes::from(format!("{}[1]", " ".repeat(100)));
+        let mut raw = Value::Array(vec![Value::Raw(
+            RawBytesValue::from_bytes(text.clone()).unwrap(),
+        )]);
+        assert_eq!(raw.referenced_bytes(&text), 3);
+        raw.compact();
+        assert_eq!(raw.referenced_bytes(&text), 0);
+        assert_eq!(raw.to_bytes(), "[[1]]");
+    }
+
+    #[test]
+    fn buffer_usage() {
+        let long = "a string longer than the inline capacity";
+        let source = Bytes::from(format!(r#"["{long}", "{long}"]"#));
+        let parsed = Value::from_bytes(source.clone()).unwrap();
+
+        let mut value = json!({
+            "static": ByteString::from_static(long),
+            "owned": long,
+            "parsed": parsed,
+        });
+        let usage = BufferUsage {
+            buffers: 2,
+            size: long.len() + source.len(),
+            referenced: 3 * long.len(),
+        };
+        assert_eq!(value.buffer_usage(), usage);
+
+        // static and owned strings, and mostly used buffers are not copied
+        value.compact();
+        assert_eq!(value.buffer_usage(), usage);
+        assert_eq!(value.referenced_bytes(&source), 2 * long.len());
+
+        // slices of buffers of the same length are told apart
+        let other = Value::from_bytes(Bytes::from(source.to_vec())).unwrap();
+        value["other"] = other[0].clone();
+        assert_eq!(value.buffer_usage().buffers, 3);
+        value.compact();
+        let usage = BufferUsage {
+            buffers: 3,
+            size: 2 * long.len() + source.len(),
+            referenced: 4 * long.len(),
+        };
+        assert_eq!(value.buffer_usage(), usage);
+        assert_eq!(value["other"], long);
+    }
+}
@@ -1,131 +1,146 @@
 use crate::lib::str::FromStr;
 use crate::map::Map;
-use crate::value::Value;
+use crate::value::{raw, shared, Value};
 use crate::{lib::*, ByteString};
 use serde::de::{
     self, Deserialize, DeserializeSeed, EnumAccess, Expected, IntoDeserializer, MapAccess,
     SeqAccess, Unexpected, VariantAccess, Visitor,
 };
-use serde::{forward_to_deserialize_any, serde_if_integer128};
+use serde::forward_to_deserialize_any;
 use serde_json::error::Error;
 use serde_json::Number;
 
 #[cfg(feature = "arbitrary_precision")]
 use crate::number::NumberFromString;
 
+/// Keeps the last value of a key that appears more than once in an object,
+/// like serde_json. [`BytesSeed::with_options`](crate::value::BytesSeed::with_options)
+/// applies another [`DuplicateKeys`](crate::DuplicateKeys) policy, and limits.
 impl<'de> Deserialize<'de> for Value {
     #[inline]
     fn deserialize<D>(deserializer: D) -> Result<Value, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        struct ValueVisitor;
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
 
-        impl<'de> Visitor<'de> for ValueVisitor {
-            type Value = Value;
+/// The visitor of `Value`, which takes the value handed over by the
+/// deserializers of this crate.
+pub(crate) struct ValueVisitor;
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("any valid JSON value")
-            }
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
 
-            #[inline]
-            fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
-                Ok(Value::Bool(value))
-            }
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
 
-            #[inline]
-            fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
-                Ok(Value::Number(value.into()))
-            }
+    #[inline]
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
 
-            #[inline]
-            fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
-                Ok(Value::Number(value.into()))
-            }
+    #[inline]
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
 
-            #[inline]
-            fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
-                Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
-            }
+    #[inline]
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
 
-            #[cfg(any(feature = "std", feature = "alloc"))]
-            #[inline]
-            fn visit_str<E>(self, value: &str) -> Result<Value, E>
-            where
-                E: serde::de::Error,
-            {
-                self.visit_string(String::from(value))
-            }
+    #[inline]
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
 
-            #[cfg(any(feature = "std", feature = "alloc"))]
-            #[inline]
-            fn visit_string<E>(self, value: String) -> Result<Value, E> {
-                Ok(Value::String(value.into()))
-            }
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Value, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_string(String::from(value))
+    }
 
-            #[inline]
-            fn visit_none<E>(self) -> Result<Value, E> {
-                Ok(Value::Null)
-            }
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[inline]
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value.into()))
+    }
 
-            #[inline]
-            fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
-            where
-                D: serde::Deserializer<'de>,
-            {
-                Deserialize::deserialize(deserializer)
-            }
+    #[inline]
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
 
-            #[inline]
-            fn visit_unit<E>(self) -> Result<Value, E> {
-                Ok(Value::Null)
-            }
+    #[inline]
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Deserialize::deserialize(deserializer)
+    }
 
-            #[inline]
-            fn visit_seq<V>(self, mut visitor: V) -> Result<Value, V::Error>
-            where
-                V: SeqAccess<'de>,
-            {
-                let mut vec = Vec::new();
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
 
-                while let Some(elem) = tri!(visitor.next_element()) {
-                    vec.push(elem);
-                }
+    #[inline]
+    fn visit_seq<V>(self, mut visitor: V) -> Result<Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut vec = Vec::new();
 
-                Ok(Value::Array(vec))
-            }
+        while let Some(elem) = tri!(visitor.next_element()) {
+            vec.push(elem);
+        }
 
-            #[cfg(any(feature = "std", feature = "alloc"))]
-            fn visit_map<V>(self, mut visitor: V) -> Result<Value, V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                match visitor.next_key_seed(KeyClassifier)? {
-                    #[cfg(feature = "arbitrary_precision")]
-                    Some(KeyClass::Number) => {
-                        let number: NumberFromString = visitor.next_value()?;
-                        Ok(Value::Number(number.value))
-                    }
-                    Some(KeyClass::Map(first_key)) => {
-                        let mut values = Map::new();
-
-                        values.insert(first_key, tri!(visitor.next_value()));
-                        while let Some((key, value)) = tri!(visitor.next_entry()) {
-                            let k: Cow<'de, str> = key;
-                            match k {
-                                Cow::Borrowed(s) => values.insert(s, value),
-                                Cow::Owned(s) => values.insert(s, value),
-                            };
-                        }
-
-                        Ok(Value::Object(values))
-                    }
-                    None => Ok(Value::Object(Map::new())),
+        Ok(Value::Array(vec))
+    }
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn visit_map<V>(self, mut visitor: V) -> Result<Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        match visitor.next_key_seed(KeyClassifier)? {
+            #[cfg(feature = "arbitrary_precision")]
+            Some(KeyClass::Number) => {
+                let number: NumberFromString = visitor.next_value()?;
+                Ok(Value::Number(number.value))
+            }
+            Some(KeyClass::Map(first_key)) => {
+                let mut values = Map::new();
+
+                values.insert(first_key, tri!(visitor.next_value()));
+                while let Some((key, value)) = tri!(visitor.next_entry()) {
+                    let k: Cow<'de, str> = key;
+                    match k {
+                        Cow::Borrowed(s) => values.insert(s, value),
+                        Cow::Owned(s) => values.insert(s, value),
+                    };
                 }
+
+                Ok(Value::Object(values))
             }
+            None => Ok(Value::Object(Map::new())),
         }
+    }
 
-        deserializer.deserialize_any(ValueVisitor)
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match shared::take() {
+            Some(value) => Ok(value),
+            None => deserializer.deserialize_any(self),
+        }
     }
 }
 
@@ -136,6 +151,17 @@ impl FromStr for Value {
     }
 }
 
+/// Forwards to a serde_json deserializer reading the text of a raw value.
+///
+/// The owned `Value` cannot lend the text to the visitor, so it is read
+/// through `io::Read` and strings are copied.
+macro_rules! deserialize_raw {
+    ($raw:expr, $method:ident($($arg:expr),*)) => {{
+        let mut de = serde_json::Deserializer::from_reader($raw.get().as_bytes());
+        serde::Deserializer::$method(&mut de, $($arg),*)
+    }};
+}
+
 macro_rules! deserialize_number {
     ($method:ident) => {
         #[cfg(not(feature = "arbitrary_precision"))]
@@ -145,6 +171,7 @@ macro_rules! deserialize_number {
         {
             match self {
                 Value::Number(n) => n.deserialize_any(visitor),
+                Value::Raw(raw) => deserialize_raw!(raw, $method(visitor)),
                 _ => Err(self.invalid_type(&visitor)),
             }
         }
@@ -156,6 +183,7 @@ macro_rules! deserialize_number {
         {
             match self {
                 Value::Number(n) => n.$method(visitor),
+                Value::Raw(raw) => deserialize_raw!(raw, $method(visitor)),
                 _ => self.deserialize_any(visitor),
             }
         }
@@ -207,6 +235,7 @@ impl<'de> serde::Deserializer<'de> for Value {
         V: Visitor<'de>,
     {
         match self {
+            _ if shared::takes::<V>(&self) => shared::hand_over(self, visitor),
             Value::Null => visitor.visit_unit(),
             Value::Bool(v) => visitor.visit_bool(v),
             Value::Number(n) => n.deserialize_any(visitor),
@@ -214,6 +243,7 @@ impl<'de> serde::Deserializer<'de> for Value {
             Value::String(v) => visitor.visit_string(v.as_str().to_string()),
             Value::Array(v) => visit_array(v, visitor),
             Value::Object(v) => visit_object(v, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_any(visitor)),
         }
     }
 
@@ -228,10 +258,8 @@ impl<'de> serde::Deserializer<'de> for Value {
     deserialize_number!(deserialize_f32);
     deserialize_number!(deserialize_f64);
 
-    serde_if_integer128! {
-        deserialize_number!(deserialize_i128);
-        deserialize_number!(deserialize_u128);
-    }
+    deserialize_number!(deserialize_i128);
+    deserialize_number!(deserialize_u128);
 
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -240,6 +268,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     {
         match self {
             Value::Null => visitor.visit_none(),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_option(visitor)),
             _ => visitor.visit_some(self),
         }
     }
@@ -247,8 +276,8 @@ impl<'de> serde::Deserializer<'de> for Value {
     #[inline]
     fn deserialize_enum<V>(
         self,
-        _name: &str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
@@ -276,6 +305,9 @@ impl<'de> serde::Deserializer<'de> for Value {
                 (variant.as_str().to_string(), Some(value))
             }
             Value::String(variant) => (variant.as_str().to_string(), None),
+            Value::Raw(raw) => {
+                return deserialize_raw!(raw, deserialize_enum(name, variants, visitor));
+            }
             other => {
                 return Err(serde::de::Error::invalid_type(
                     other.unexpected(),
@@ -296,8 +328,11 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        let _ = name;
-        visitor.visit_newtype_struct(self)
+        match self {
+            Value::Raw(_) if name == raw::TOKEN => shared::hand_over(self, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_newtype_struct(name, visitor)),
+            _ => visitor.visit_newtype_struct(self),
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -306,6 +341,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     {
         match self {
             Value::Bool(v) => visitor.visit_bool(v),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_bool(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -329,8 +365,10 @@ impl<'de> serde::Deserializer<'de> for Value {
         V: Visitor<'de>,
     {
         match self {
+            _ if shared::takes::<V>(&self) => shared::hand_over(self, visitor),
             #[cfg(any(feature = "std", feature = "alloc"))]
             Value::String(v) => visitor.visit_string(v.as_str().to_string()),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_string(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -350,6 +388,7 @@ impl<'de> serde::Deserializer<'de> for Value {
             #[cfg(any(feature = "std", feature = "alloc"))]
             Value::String(v) => visitor.visit_string(v.as_str().to_string()),
             Value::Array(v) => visit_array(v, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_byte_buf(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -360,6 +399,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     {
         match self {
             Value::Null => visitor.visit_unit(),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_unit(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -377,6 +417,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     {
         match self {
             Value::Array(v) => visit_array(v, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_seq(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -406,14 +447,15 @@ impl<'de> serde::Deserializer<'de> for Value {
     {
         match self {
             Value::Object(v) => visit_object(v, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_map(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
@@ -422,6 +464,7 @@ impl<'de> serde::Deserializer<'de> for Value {
         match self {
             Value::Array(v) => visit_array(v, visitor),
             Value::Object(v) => visit_object(v, visitor),
+            Value::Raw(raw) => deserialize_raw!(raw, deserialize_struct(name, fields, visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -595,7 +638,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
             Some((key, value)) => {
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer {
-                    key: Cow::Owned(key.as_str().to_string()),
+                    key: MapKey::Owned(key),
                 };
                 seed.deserialize(key_de).map(Some)
             }
@@ -621,6 +664,14 @@ impl<'de> MapAccess<'de> for MapDeserializer {
     }
 }
 
+/// Forwards to a serde_json deserializer borrowing the text of a raw value.
+macro_rules! deserialize_raw_ref {
+    ($raw:expr, $method:ident($($arg:expr),*)) => {{
+        let mut de = serde_json::Deserializer::from_str($raw.get());
+        serde::Deserializer::$method(&mut de, $($arg),*)
+    }};
+}
+
 macro_rules! deserialize_value_ref_number {
     ($method:ident) => {
         #[cfg(not(feature = "arbitrary_precision"))]
@@ -630,6 +681,7 @@ macro_rules! deserialize_value_ref_number {
         {
             match *self {
                 Value::Number(ref n) => n.deserialize_any(visitor),
+                Value::Raw(ref raw) => deserialize_raw_ref!(raw, $method(visitor)),
                 _ => Err(self.invalid_type(&visitor)),
             }
         }
@@ -641,6 +693,7 @@ macro_rules! deserialize_value_ref_number {
         {
             match *self {
                 Value::Number(ref n) => n.$method(visitor),
+                Value::Raw(ref raw) => deserialize_raw_ref!(raw, $method(visitor)),
                 _ => self.deserialize_any(visitor),
             }
         }
@@ -694,12 +747,14 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         V: Visitor<'de>,
     {
         match *self {
+            _ if shared::takes::<V>(self) => shared::hand_over(self.clone(), visitor),
             Value::Null => visitor.visit_unit(),
             Value::Bool(v) => visitor.visit_bool(v),
             Value::Number(ref n) => n.deserialize_any(visitor),
             Value::String(ref v) => visitor.visit_borrowed_str(v.as_str()),
             Value::Array(ref v) => visit_array_ref(v, visitor),
             Value::Object(ref v) => visit_object_ref(v, visitor),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_any(visitor)),
         }
     }
 
@@ -714,10 +769,8 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     deserialize_value_ref_number!(deserialize_f32);
     deserialize_value_ref_number!(deserialize_f64);
 
-    serde_if_integer128! {
-        deserialize_number!(deserialize_i128);
-        deserialize_number!(deserialize_u128);
-    }
+    deserialize_number!(deserialize_i128);
+    deserialize_number!(deserialize_u128);
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
     where
@@ -725,14 +778,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match *self {
             Value::Null => visitor.visit_none(),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_option(visitor)),
             _ => visitor.visit_some(self),
         }
     }
 
     fn deserialize_enum<V>(
         self,
-        _name: &str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
@@ -760,6 +814,9 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
                 (variant.as_str(), Some(value))
             }
             Value::String(ref variant) => (variant.as_str(), None),
+            Value::Raw(ref raw) => {
+                return deserialize_raw_ref!(raw, deserialize_enum(name, variants, visitor));
+            }
             ref other => {
                 return Err(serde::de::Error::invalid_type(
                     other.unexpected(),
@@ -780,8 +837,13 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     where
         V: Visitor<'de>,
     {
-        let _ = name;
-        visitor.visit_newtype_struct(self)
+        match *self {
+            Value::Raw(_) if name == raw::TOKEN => shared::hand_over(self.clone(), visitor),
+            Value::Raw(ref raw) => {
+                deserialize_raw_ref!(raw, deserialize_newtype_struct(name, visitor))
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -790,6 +852,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match *self {
             Value::Bool(v) => visitor.visit_bool(v),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_bool(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -806,7 +869,9 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         V: Visitor<'de>,
     {
         match *self {
+            _ if shared::takes::<V>(self) => shared::hand_over(self.clone(), visitor),
             Value::String(ref v) => visitor.visit_borrowed_str(v.as_str()),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_str(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -825,6 +890,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         match *self {
             Value::String(ref v) => visitor.visit_borrowed_str(v.as_str()),
             Value::Array(ref v) => visit_array_ref(v, visitor),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_bytes(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -842,6 +908,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match *self {
             Value::Null => visitor.visit_unit(),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_unit(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -859,6 +926,7 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match *self {
             Value::Array(ref v) => visit_array_ref(v, visitor),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_seq(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -888,14 +956,15 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
     {
         match *self {
             Value::Object(ref v) => visit_object_ref(v, visitor),
+            Value::Raw(ref raw) => deserialize_raw_ref!(raw, deserialize_map(visitor)),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
@@ -904,6 +973,9 @@ impl<'de> serde::Deserializer<'de> for &'de Value {
         match *self {
             Value::Array(ref v) => visit_array_ref(v, visitor),
             Value::Object(ref v) => visit_object_ref(v, visitor),
+            Value::Raw(ref raw) => {
+                deserialize_raw_ref!(raw, deserialize_struct(name, fields, visitor))
+            }
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -1066,7 +1138,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
             Some((key, value)) => {
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer {
-                    key: Cow::Borrowed(key.as_str()),
+                    key: MapKey::Borrowed(key),
                 };
                 seed.deserialize(key_de).map(Some)
             }
@@ -1093,7 +1165,38 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
 }
 
 struct MapKeyDeserializer<'de> {
-    key: Cow<'de, str>,
+    key: MapKey<'de>,
+}
+
+enum MapKey<'de> {
+    Borrowed(&'de ByteString),
+    Owned(ByteString),
+}
+
+impl<'de> MapKey<'de> {
+    fn as_str(&self) -> &str {
+        match self {
+            MapKey::Borrowed(key) => key.as_str(),
+            MapKey::Owned(key) => key.as_str(),
+        }
+    }
+
+    fn visit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            MapKey::Borrowed(key) => visitor.visit_borrowed_str(key.as_str()),
+            MapKey::Owned(key) => visitor.visit_string(String::from(key)),
+        }
+    }
+
+    fn into_owned(self) -> ByteString {
+        match self {
+            MapKey::Borrowed(key) => key.clone(),
+            MapKey::Owned(key) => key,
+        }
+    }
 }
 
 macro_rules! deserialize_integer_key {
@@ -1102,11 +1205,9 @@ macro_rules! deserialize_integer_key {
         where
             V: Visitor<'de>,
         {
-            match (self.key.parse(), self.key) {
-                (Ok(integer), _) => visitor.$visit(integer),
-                (Err(_), Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
-                #[cfg(any(feature = "std", feature = "alloc"))]
-                (Err(_), Cow::Owned(s)) => visitor.visit_string(s),
+            match self.key.as_str().parse() {
+                Ok(integer) => visitor.$visit(integer),
+                Err(_) => self.key.visit(visitor),
             }
         }
     };
@@ -1119,7 +1220,10 @@ impl<'de> serde::Deserializer<'de> for MapKeyDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        BorrowedCowStrDeserializer::new(self.key).deserialize_any(visitor)
+        if shared::takes_string::<V>() {
+            return shared::hand_over(Value::String(self.key.into_owned()), visitor);
+        }
+        self.key.visit(visitor)
     }
 
     deserialize_integer_key!(deserialize_i8 => visit_i8);
@@ -1131,10 +1235,8 @@ impl<'de> serde::Deserializer<'de> for MapKeyDeserializer<'de> {
     deserialize_integer_key!(deserialize_u32 => visit_u32);
     deserialize_integer_key!(deserialize_u64 => visit_u64);
 
-    serde_if_integer128! {
-        deserialize_integer_key!(deserialize_i128 => visit_i128);
-        deserialize_integer_key!(deserialize_u128 => visit_u128);
-    }
+    deserialize_integer_key!(deserialize_i128 => visit_i128);
+    deserialize_integer_key!(deserialize_u128 => visit_u128);
 
     #[inline]
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
@@ -1166,9 +1268,11 @@ impl<'de> serde::Deserializer<'de> for MapKeyDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.key
-            .into_deserializer()
-            .deserialize_enum(name, variants, visitor)
+        let key = match self.key {
+            MapKey::Borrowed(key) => Cow::Borrowed(key.as_str()),
+            MapKey::Owned(key) => Cow::Owned(String::from(key)),
+        };
+        BorrowedCowStrDeserializer::new(key).deserialize_enum(name, variants, visitor)
     }
 
     forward_to_deserialize_any! {
@@ -1237,7 +1341,7 @@ impl Value {
     }
 
     #[cold]
-    fn unexpected(&self) -> Unexpected {
+    fn unexpected(&self) -> Unexpected<'_> {
         match *self {
             Value::Null => Unexpected::Unit,
             Value::Bool(b) => Unexpected::Bool(b),
@@ -1256,6 +1360,7 @@ impl Value {
             Value::String(ref s) => Unexpected::Str(s.as_str()),
             Value::Array(_) => Unexpected::Seq,
             Value::Object(_) => Unexpected::Map,
+            Value::Raw(_) => Unexpected::Other("raw value"),
         }
     }
 }
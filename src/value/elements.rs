@@ -0,0 +1,365 @@
+use super::parse_index;
+use super::stream::Builder;
+use crate::lib::*;
+use crate::parser::{Event, Parser};
+use crate::{ByteString, ParseOptions, Value};
+use bytes::Bytes;
+use serde::de::Error as _;
+use serde_json::Error;
+
+impl Value {
+    /// Parses the elements of a top-level JSON array one at a time, without
+    /// building the array itself.
+    ///
+    /// Only the element being returned is kept in memory, and its strings
+    /// reference `data`. The whole document is still validated: a syntax
+    /// error is returned after the elements preceding it.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, Value};
+    /// let data = Bytes::from_static(br#"[{"id": 1}, {"id": 2}]"#);
+    /// let mut ids = Vec::new();
+    /// for element in Value::iter_array_bytes(data) {
+    ///     ids.push(element?["id"].clone());
+    /// }
+    ///
+    /// assert_eq!(ids, [json!(1), json!(2)]);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn iter_array_bytes(data: Bytes) -> ArrayElements {
+        Value::iter_array_bytes_at(data, "")
+    }
+
+    /// Parses the elements of the array at the JSON Pointer `pointer` one at
+    /// a time, like [`Value::iter_array_bytes`].
+    ///
+    /// The rest of the document is validated but not built. If the pointer
+    /// does not lead to an array, the iterator returns an error.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, Value};
+    /// let data = Bytes::from_static(br#"{"data": {"items": ["a", "b"]}, "errors": []}"#);
+    /// let items = Value::iter_array_bytes_at(data, "/data/items").collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(items, [json!("a"), json!("b")]);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn iter_array_bytes_at(data: Bytes, pointer: &str) -> ArrayElements {
+        Value::iter_array_bytes_with_options(data, pointer, &ParseOptions::new())
+    }
+
+    /// Parses the elements of the array at the JSON Pointer `pointer` one at
+    /// a time, like [`Value::iter_array_bytes_at`], configured by `options`.
+    /// An empty pointer designates a top-level array.
+    ///
+    /// The limits apply to the whole document, elements included.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, DuplicateKeys, ParseOptions, Value};
+    /// let data = Bytes::from_static(br#"[{"id": 1}, {"id": 2, "id": 3}]"#);
+    /// let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Reject);
+    /// let mut elements = Value::iter_array_bytes_with_options(data, "", &options);
+    ///
+    /// assert_eq!(elements.next().unwrap()?, json!({"id": 1}));
+    /// assert!(elements.next().unwrap().is_err());
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn iter_array_bytes_with_options(
+        data: Bytes,
+        pointer: &str,
+        options: &ParseOptions,
+    ) -> ArrayElements {
+        let mut parser = Parser::new(options.clone());
+        // reported by the first call to `next`
+        let error = parser.feed(data).err();
+        parser.end();
+
+        let tokens = if pointer.starts_with('/') {
+            pointer
+                .split('/')
+                .skip(1)
+                .map(|token| token.replace("~1", "/").replace("~0", "~"))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        ArrayElements {
+            parser,
+            builder: Builder::new(options.duplicate_keys),
+            error,
+            valid_pointer: pointer.is_empty() || pointer.starts_with('/'),
+            pointer: pointer.to_owned(),
+            tokens,
+            stack: Vec::new(),
+            state: State::Seeking,
+        }
+    }
+}
+
+/// An iterator over the elements of a JSON array, created by
+/// [`Value::iter_array_bytes`], [`Value::iter_array_bytes_at`] and
+/// [`Value::iter_array_bytes_with_options`].
+pub struct ArrayElements {
+    parser: Parser,
+    builder: Builder,
+    /// Error found when feeding the input, like going over its maximum size.
+    error: Option<Error>,
+    valid_pointer: bool,
+    pointer: String,
+    /// Unescaped tokens of `pointer`.
+    tokens: Vec<String>,
+    /// Containers enclosing the current value, while seeking the array.
+    stack: Vec<Frame>,
+    state: State,
+}
+
+struct Frame {
+    /// This container is at a prefix of the pointer.
+    on_path: bool,
+    /// The key of the current entry, for an object.
+    key: Option<ByteString>,
+    /// Number of elements started so far, for an array.
+    len: Option<usize>,
+}
+
+enum State {
+    Seeking,
+    /// Inside the array, `depth` containers deep in the current element.
+    Elements {
+        depth: usize,
+    },
+    /// The array is complete, the rest of the document is validated.
+    Rest,
+    Done,
+}
+
+impl ArrayElements {
+    /// Updates the path of the current value, and returns whether `event`
+    /// starts the array the pointer leads to.
+    fn seek(&mut self, event: &Event) -> Result<bool, Error> {
+        let starts = match event {
+            Event::Key(key) => {
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.key = Some(key.clone());
+                }
+                return Ok(false);
+            }
+            Event::EndArray | Event::EndObject => {
+                self.stack.pop();
+                return Ok(false);
+            }
+            Event::StartArray | Event::StartObject => true,
+            _ => false,
+        };
+
+        let depth = self.stack.len();
+        let on_path = match self.stack.last_mut() {
+            None => self.valid_pointer,
+            Some(frame) => {
+                let token = frame.on_path.then(|| &self.tokens[depth - 1]);
+                match &mut frame.len {
+                    Some(len) => {
+                        *len += 1;
+                        token.is_some_and(|t| parse_index(t) == Some(*len - 1))
+                    }
+                    None => {
+                        token.is_some_and(|t| frame.key.as_ref().map(ByteString::as_str) == Some(t))
+                    }
+                }
+            }
+        };
+
+        if on_path && depth == self.tokens.len() {
+            return match event {
+                Event::StartArray => Ok(true),
+                _ => Err(Error::custom(format_args!(
+                    "the value at {:?} is not an array",
+                    self.pointer
+                ))),
+            };
+        }
+        if starts {
+            self.stack.push(Frame {
+                on_path,
+                key: None,
+                len: matches!(event, Event::StartArray).then_some(0),
+            });
+        }
+        Ok(false)
+    }
+
+    fn next_element(&mut self) -> Result<Option<Value>, Error> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        while let Some(event) = tri!(self.parser.next()) {
+            match self.state {
+                State::Seeking => {
+                    if tri!(self.seek(&event)) {
+                        self.stack.clear();
+                        self.state = State::Elements { depth: 0 };
+                    }
+                }
+                State::Elements { ref mut depth } => {
+                    match event {
+                        Event::EndArray if *depth == 0 => {
+                            self.state = State::Rest;
+                            continue;
+                        }
+                        Event::StartArray | Event::StartObject => *depth += 1,
+                        Event::EndArray | Event::EndObject => *depth -= 1,
+                        _ => {}
+                    }
+                    match self.builder.push(event) {
+                        Ok(Some(value)) => return Ok(Some(value)),
+                        Ok(None) => {}
+                        Err(code) => return Err(self.parser.key_error(&code)),
+                    }
+                }
+                State::Rest => {}
+                State::Done => unreachable!(),
+            }
+        }
+
+        match self.state {
+            State::Seeking => Err(Error::custom(format_args!(
+                "no value at {:?}",
+                self.pointer
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Iterator for ArrayElements {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let State::Done = self.state {
+            return None;
+        }
+        match self.next_element() {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.state = State::Done;
+                None
+            }
+            Err(err) => {
+                self.state = State::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, DuplicateKeys, ParseOptions, Value};
+    use bytes::Bytes;
+
+    fn collect(data: &'static str, pointer: &str) -> Vec<Result<Value, String>> {
+        Value::iter_array_bytes_at(Bytes::from_static(data.as_bytes()), pointer)
+            .map(|element| element.map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn elements() {
+        let data = Bytes::from_static(br#" [{"a": ["b"]}, "c", 1, [[]], null] "#);
+        let elements: Vec<Value> = Value::iter_array_bytes(data.clone())
+            .map(Result::unwrap)
+            .collect();
+        let expected = Value::from_bytes(data.clone()).unwrap();
+        assert_eq!(Value::Array(elements.clone()), expected);
+        let b = elements[0]["a"][0].as_str().unwrap();
+        assert!(data.as_ptr_range().contains(&b.as_ptr()));
+
+        assert_eq!(collect("[]", ""), []);
+        let data = r#"{"a": [0, {"b": [1]}], "a~/": {"a": [2, 3]}, "c": [4], "d": {"e": 5}}"#;
+        assert_eq!(collect(data, "/a/1/b"), [Ok(json!(1))]);
+        assert_eq!(collect(data, "/a~0~1/a"), [Ok(json!(2)), Ok(json!(3))]);
+        assert_eq!(collect(data, "/c"), [Ok(json!(4))]);
+    }
+
+    #[test]
+    fn options() {
+        let collect = |data: &'static str, options: &ParseOptions| {
+            Value::iter_array_bytes_with_options(Bytes::from_static(data.as_bytes()), "/a", options)
+                .map(|element| element.map_err(|e| e.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let options = ParseOptions::new().lenient(true);
+        assert_eq!(
+            collect("{a: [1, 'b',], c: 0x2}", &options),
+            [Ok(json!(1)), Ok(json!("b"))]
+        );
+
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeys::KeepFirst);
+        assert_eq!(
+            collect(r#"{"a": [{"b": 1, "b": 2}]}"#, &options),
+            [Ok(json!({"b": 1}))]
+        );
+
+        let options = ParseOptions::new().max_depth(3);
+        assert_eq!(
+            collect(r#"{"a": [[1], [[2]]]}"#, &options),
+            [
+                Ok(json!([1])),
+                Err("maximum depth of 3 exceeded at line 1 column 14".to_owned())
+            ]
+        );
+
+        let options = ParseOptions::new().max_size(4);
+        assert_eq!(
+            collect(r#"{"a": []}"#, &options),
+            [Err(
+                "maximum input size of 4 bytes exceeded at line 1 column 5".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let err = |msg: &str| vec![Err(msg.to_owned())];
+        let data = r#"{"a": [1, {"b": 2}], "c": 3}"#;
+        assert_eq!(
+            collect(data, "/c"),
+            err("the value at \"/c\" is not an array")
+        );
+        assert_eq!(
+            collect(data, "/a/1/b"),
+            err("the value at \"/a/1/b\" is not an array")
+        );
+        assert_eq!(collect(data, "/a/2"), err("no value at \"/a/2\""));
+        assert_eq!(collect(data, "a"), err("no value at \"a\""));
+
+        let data = r#"{"a": [1, {"b": 2}], "c": 3, "d": [4, 5 6]}"#;
+        assert_eq!(
+            collect(data, "/d"),
+            [
+                Ok(json!(4)),
+                Ok(json!(5)),
+                Err("expected `,` or `]` at line 1 column 41".to_owned())
+            ]
+        );
+        assert_eq!(
+            collect(data, "/a"),
+            [
+                Ok(json!(1)),
+                Ok(json!({"b": 2})),
+                Err("expected `,` or `]` at line 1 column 41".to_owned())
+            ]
+        );
+        assert_eq!(
+            collect("[1] 2", ""),
+            [
+                Ok(json!(1)),
+                Err("trailing characters at line 1 column 5".to_owned())
+            ]
+        );
+    }
+}
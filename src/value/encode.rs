@@ -0,0 +1,286 @@
+//! Direct JSON encoding of a [`Value`] into a [`BytesMut`].
+//!
+//! The output is byte for byte identical to what `serde_json::to_writer`
+//! produces for the same value, but it is written in two passes: the first
+//! one computes the exact encoded length, so that the buffer is allocated
+//! once, the second one copies the data.
+//!
+//! Strings parsed with their escape sequences left in place are the
+//! exception: they are written back verbatim, escaped as in the source.
+//! Strings parsed without escape sequences are known not to need any, so
+//! they are copied without being scanned.
+
+use crate::lib::fmt::{self, Write};
+use crate::value::Value;
+use crate::ByteString;
+use bytes::{BufMut, BytesMut};
+use serde_json::Number;
+
+const BB: u8 = b'b'; // \x08
+const TT: u8 = b't'; // \x09
+const NN: u8 = b'n'; // \x0A
+const FF: u8 = b'f'; // \x0C
+const RR: u8 = b'r'; // \x0D
+const QU: u8 = b'"'; // \x22
+const BS: u8 = b'\\'; // \x5C
+const UU: u8 = b'u'; // \x00...\x1F except the ones above
+const __: u8 = 0;
+
+// Lookup table of escape sequences. A value of b'x' at index i means that byte
+// i is escaped as "\x" in JSON. A value of 0 means that byte i is not escaped.
+// This is the same table serde_json uses, so both serializers agree.
+static ESCAPE: [u8; 256] = [
+    //   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
+    UU, UU, UU, UU, UU, UU, UU, UU, BB, TT, NN, UU, FF, RR, UU, UU, // 0
+    UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, // 1
+    __, __, QU, __, __, __, __, __, __, __, __, __, __, __, __, __, // 2
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 3
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 4
+    __, __, __, __, __, __, __, __, __, __, __, __, BS, __, __, __, // 5
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 6
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 7
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 8
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // 9
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // A
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // B
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // C
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // D
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // E
+    __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, __, // F
+];
+
+static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+
+/// Returns the exact number of bytes `write_value` will append for `value`.
+pub(crate) fn value_len(value: &Value) -> usize {
+    match *value {
+        Value::Null => 4,
+        Value::Bool(true) => 4,
+        Value::Bool(false) => 5,
+        Value::Number(ref n) => number_len(n),
+        Value::String(ref s) => string_len(s),
+        Value::Array(ref v) => {
+            // brackets, plus one comma between each element
+            let mut len = 2 + v.len().saturating_sub(1);
+            for elem in v {
+                len += value_len(elem);
+            }
+            len
+        }
+        Value::Object(ref m) => {
+            // braces, one colon per entry, and one comma between each entry
+            let mut len = 2 + m.len() + m.len().saturating_sub(1);
+            for (k, v) in m {
+                len += string_len(k) + value_len(v);
+            }
+            len
+        }
+        Value::Raw(ref raw) => raw.inner().len(),
+    }
+}
+
+/// Appends the compact JSON representation of `value` to `out`.
+///
+/// The buffer grows as needed, but callers are expected to reserve
+/// `value_len(value)` bytes beforehand.
+pub(crate) fn write_value(value: &Value, out: &mut BytesMut) {
+    match *value {
+        Value::Null => out.put_slice(b"null"),
+        Value::Bool(true) => out.put_slice(b"true"),
+        Value::Bool(false) => out.put_slice(b"false"),
+        Value::Number(ref n) => write_number(n, out),
+        Value::String(ref s) => write_string(s, out),
+        Value::Array(ref v) => {
+            out.put_u8(b'[');
+            let mut first = true;
+            for elem in v {
+                if !first {
+                    out.put_u8(b',');
+                }
+                first = false;
+                write_value(elem, out);
+            }
+            out.put_u8(b']');
+        }
+        Value::Object(ref m) => {
+            out.put_u8(b'{');
+            let mut first = true;
+            for (k, v) in m {
+                if !first {
+                    out.put_u8(b',');
+                }
+                first = false;
+                write_string(k, out);
+                out.put_u8(b':');
+                write_value(v, out);
+            }
+            out.put_u8(b'}');
+        }
+        Value::Raw(ref raw) => out.put_slice(raw.inner()),
+    }
+}
+
+fn string_len(value: &ByteString) -> usize {
+    match value.raw_escaped().or(value.escape_free_bytes()) {
+        Some(raw) => 2 + raw.len(),
+        None => str_len(value.as_str()),
+    }
+}
+
+pub(crate) fn write_string(value: &ByteString, out: &mut BytesMut) {
+    match value.raw_escaped().or(value.escape_free_bytes()) {
+        Some(raw) => {
+            out.put_u8(b'"');
+            out.put_slice(raw);
+            out.put_u8(b'"');
+        }
+        None => write_str(value.as_str(), out),
+    }
+}
+
+/// Returns the length of `value` once quoted and escaped.
+pub(crate) fn str_len(value: &str) -> usize {
+    let mut len = 2;
+    for &byte in value.as_bytes() {
+        len += match ESCAPE[byte as usize] {
+            0 => 1,
+            UU => 6,
+            _ => 2,
+        };
+    }
+    len
+}
+
+/// Appends `value` to `out` as a quoted and escaped JSON string.
+pub(crate) fn write_str(value: &str, out: &mut BytesMut) {
+    let bytes = value.as_bytes();
+    out.put_u8(b'"');
+
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let escape = ESCAPE[byte as usize];
+        if escape == __ {
+            continue;
+        }
+
+        if start < i {
+            out.put_slice(&bytes[start..i]);
+        }
+
+        match escape {
+            UU => out.put_slice(&[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX_DIGITS[(byte >> 4) as usize],
+                HEX_DIGITS[(byte & 0xF) as usize],
+            ]),
+            _ => out.put_slice(&[b'\\', escape]),
+        }
+
+        start = i + 1;
+    }
+
+    if start != bytes.len() {
+        out.put_slice(&bytes[start..]);
+    }
+    out.put_u8(b'"');
+}
+
+fn number_len(n: &Number) -> usize {
+    struct Counter(usize);
+
+    impl Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    // `Display for Number` uses the same itoa/ryu formatting as the
+    // serializer, and writing to a counter cannot fail
+    let _ = write!(counter, "{n}");
+    counter.0
+}
+
+fn write_number(n: &Number, out: &mut BytesMut) {
+    // writing to a `BytesMut` cannot fail, it grows as needed
+    let _ = write!(out, "{n}");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json, ParseOptions, Value};
+    use bytes::{Bytes, BytesMut};
+
+    #[track_caller]
+    fn check(value: Value) {
+        let expected = serde_json::to_string(&value).unwrap();
+        assert_eq!(super::value_len(&value), expected.len());
+
+        let bytes = value.to_bytes();
+        assert_eq!(bytes, expected.as_bytes());
+        assert_eq!(bytes.len(), expected.len());
+    }
+
+    #[test]
+    fn matches_serde_json() {
+        check(json!(null));
+        check(json!(true));
+        check(json!(false));
+        check(json!(0));
+        check(json!(-12));
+        check(json!(u64::MAX));
+        check(json!(i64::MIN));
+        check(json!(1.5));
+        check(json!(-0.0));
+        check(json!(1e300));
+        check(json!(""));
+        check(json!([]));
+        check(json!({}));
+        check(json!({
+            "a": [1, 2.5, "three", null, {"b": false}],
+            "c": {"d": {}, "e": []},
+        }));
+    }
+
+    #[test]
+    fn escapes() {
+        check(json!("\"quoted\" \\ back/slash"));
+        check(json!("\u{0}\u{1}\u{8}\t\n\u{b}\u{c}\r\u{1f}\u{7f}"));
+        check(json!("unicode: é 日本 🦀"));
+        check(json!({"key\nwith\"escapes": "value\u{2028}"}));
+    }
+
+    #[test]
+    fn escape_free() {
+        let escape_free = |value: &Value| match value {
+            Value::String(s) => s.escape_free_bytes().is_some(),
+            _ => panic!("expected a string"),
+        };
+        let input = Bytes::from_static(br#"{"plain": "a/b", "escaped": "a\"b", "single": 'a"b'}"#);
+        let options = ParseOptions::new().lenient(true);
+        let value = Value::from_bytes_with_options(input, &options).unwrap();
+        assert!(escape_free(&value["plain"]));
+        assert!(!escape_free(&value["escaped"]));
+        assert!(!escape_free(&value["single"]));
+        assert!(!escape_free(&json!("a")));
+        check(value.clone());
+        assert_eq!(value["single"].to_bytes(), r#""a\"b""#);
+
+        let value = Value::from_bytes(Bytes::from_static(br#"["a", "\n"]"#)).unwrap();
+        assert!(escape_free(&value[0]));
+        assert!(!escape_free(&value[1]));
+        check(value);
+    }
+
+    #[test]
+    fn to_bytes_into_appends() {
+        let mut buf = BytesMut::from(&b"prefix:"[..]);
+        json!({"a": 1}).to_bytes_into(&mut buf);
+        json!([true]).to_bytes_into(&mut buf);
+        assert_eq!(&buf[..], b"prefix:{\"a\":1}[true]");
+    }
+}
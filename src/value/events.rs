@@ -0,0 +1,233 @@
+use super::stream::Builder;
+use crate::lib::*;
+use crate::parser::Parser;
+use crate::{DuplicateKeys, ParseOptions, Value};
+use bytes::Bytes;
+use serde_json::Error;
+
+pub use crate::parser::Event;
+
+/// A pull parser returning the [`Event`]s of a JSON document one by one,
+/// without building a tree.
+///
+/// Strings and keys are slices of the input, unless they contain escape
+/// sequences. Subtrees that are not needed can be skipped with
+/// [`skip_value`](EventReader::skip_value), or built with
+/// [`read_value`](EventReader::read_value).
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, Event, EventReader};
+/// let response = Bytes::from_static(
+///     br#"{"data": {"big": [1, 2, 3]}, "errors": [{"message": "oops"}]}"#,
+/// );
+/// let mut reader = EventReader::new(response);
+/// let mut errors = None;
+///
+/// assert_eq!(reader.next_event()?, Some(Event::StartObject));
+/// while let Some(Event::Key(key)) = reader.next_event()? {
+///     if key.as_str() == "errors" {
+///         errors = reader.read_value()?;
+///     } else {
+///         reader.skip_value()?;
+///     }
+/// }
+///
+/// assert_eq!(errors, Some(json!([{"message": "oops"}])));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub struct EventReader {
+    parser: Parser,
+    duplicate_keys: DuplicateKeys,
+    /// Error found before parsing.
+    error: Option<Error>,
+    done: bool,
+}
+
+impl EventReader {
+    /// Creates a reader over a complete document.
+    pub fn new(data: Bytes) -> Self {
+        EventReader::with_options(data, ParseOptions::new())
+    }
+
+    /// Creates a reader over a complete document, parsed with `options`.
+    /// The duplicate key policy is only applied by
+    /// [`read_value`](EventReader::read_value).
+    pub fn with_options(data: Bytes, options: ParseOptions) -> Self {
+        let duplicate_keys = options.duplicate_keys;
+        let mut parser = Parser::new(options);
+        let error = parser.feed(data).err();
+        parser.end();
+        EventReader {
+            parser,
+            duplicate_keys,
+            error,
+            done: false,
+        }
+    }
+
+    /// Returns the next event, or `None` at the end of the document.
+    ///
+    /// Syntax errors are returned when they are reached, after the events
+    /// preceding them. The reader returns `None` after an error.
+    pub fn next_event(&mut self) -> Result<Option<Event>, Error> {
+        if let Some(err) = self.error.take() {
+            self.done = true;
+            return Err(err);
+        }
+        if self.done {
+            return Ok(None);
+        }
+        match self.parser.next() {
+            Ok(Some(event)) => Ok(Some(event)),
+            Ok(None) => {
+                self.done = true;
+                Ok(None)
+            }
+            Err(err) => {
+                self.done = true;
+                Err(err)
+            }
+        }
+    }
+
+    /// Skips the next value, with all its contents. After an
+    /// [`Event::Key`], this skips the value of that key.
+    ///
+    /// If the next event ends the enclosing array or object, only that event
+    /// is consumed.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        let mut depth = 0usize;
+        while let Some(event) = tri!(self.next_event()) {
+            match event {
+                Event::StartArray | Event::StartObject => depth += 1,
+                Event::EndArray | Event::EndObject if depth == 0 => break,
+                Event::EndArray | Event::EndObject => depth -= 1,
+                Event::Key(_) => continue,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the next value. After an [`Event::Key`], this returns the value
+    /// of that key.
+    ///
+    /// Returns `None` at the end of the document, or if the next event ends
+    /// the enclosing array or object, which is consumed. This reads the
+    /// elements of an array after its [`Event::StartArray`]:
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, Event, EventReader};
+    /// let mut reader = EventReader::new(Bytes::from_static(br#"[{"a": 1}, [2]]"#));
+    /// assert_eq!(reader.next_event()?, Some(Event::StartArray));
+    ///
+    /// let mut elements = Vec::new();
+    /// while let Some(element) = reader.read_value()? {
+    ///     elements.push(element);
+    /// }
+    /// assert_eq!(elements, [json!({"a": 1}), json!([2])]);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn read_value(&mut self) -> Result<Option<Value>, Error> {
+        let mut builder = Builder::new(self.duplicate_keys);
+        let mut started = false;
+        while let Some(event) = tri!(self.next_event()) {
+            if !started && matches!(event, Event::EndArray | Event::EndObject) {
+                break;
+            }
+            started = true;
+            match builder.push(event) {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) => {}
+                Err(code) => return Err(self.parser.key_error(&code)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for EventReader {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, EventReader};
+    use crate::{json, ByteString, DuplicateKeys, ParseOptions};
+    use bytes::Bytes;
+    use serde_json::Number;
+
+    #[test]
+    fn events() {
+        let data = Bytes::from_static(br#"{"a": ["b\n", 1.5, true, null], "c": {}}"#);
+        let events: Vec<Event> = EventReader::new(data.clone()).map(Result::unwrap).collect();
+        assert_eq!(
+            events,
+            [
+                Event::StartObject,
+                Event::Key(ByteString::from("a")),
+                Event::StartArray,
+                Event::String(ByteString::from("b\n")),
+                Event::Number(Number::from_f64(1.5).unwrap()),
+                Event::Bool(true),
+                Event::Null,
+                Event::EndArray,
+                Event::Key(ByteString::from("c")),
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndObject,
+            ]
+        );
+        let Event::Key(a) = &events[1] else {
+            unreachable!()
+        };
+        assert!(data.as_ptr_range().contains(&a.as_str().as_ptr()));
+
+        let mut reader = EventReader::new(data);
+        reader.next_event().unwrap();
+        reader.next_event().unwrap();
+        reader.skip_value().unwrap();
+        assert_eq!(reader.next_event().unwrap(), Some(Event::Key("c".into())));
+        assert_eq!(reader.read_value().unwrap(), Some(json!({})));
+        assert_eq!(reader.read_value().unwrap(), None);
+        assert_eq!(reader.read_value().unwrap(), None);
+    }
+
+    #[test]
+    fn errors() {
+        let mut reader = EventReader::new(Bytes::from_static(b"[1, }"));
+        assert_eq!(reader.next_event().unwrap(), Some(Event::StartArray));
+        reader.skip_value().unwrap();
+        assert_eq!(
+            reader.skip_value().unwrap_err().to_string(),
+            "expected value at line 1 column 5"
+        );
+        assert_eq!(reader.next_event().unwrap(), None);
+
+        let options = ParseOptions::new()
+            .max_size(2)
+            .duplicate_keys(DuplicateKeys::Reject);
+        let mut reader = EventReader::with_options(Bytes::from_static(b"[1]"), options.clone());
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().to_string(),
+            "maximum input size of 2 bytes exceeded at line 1 column 3"
+        );
+        assert!(reader.next().is_none());
+
+        let data = Bytes::from_static(br#"{"a": 1, "a": 2}"#);
+        let mut reader = EventReader::with_options(data, options.max_size(100));
+        assert_eq!(
+            reader.read_value().unwrap_err().to_string(),
+            "duplicate key \"a\" in the root object at line 1 column 12"
+        );
+    }
+}
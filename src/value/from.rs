@@ -1,12 +1,9 @@
-use super::Value;
+use super::{RawBytesValue, Value};
 use crate::lib::iter::FromIterator;
 use crate::map::Map;
 use crate::{lib::*, ByteString};
 use serde_json::Number;
 
-#[cfg(feature = "arbitrary_precision")]
-use serde::serde_if_integer128;
-
 macro_rules! from_integer {
     ($($ty:ident)*) => {
         $(
@@ -25,10 +22,8 @@ from_integer! {
 }
 
 #[cfg(feature = "arbitrary_precision")]
-serde_if_integer128! {
-    from_integer! {
-        i128 u128
-    }
+from_integer! {
+    i128 u128
 }
 
 impl From<f32> for Value {
@@ -136,6 +131,13 @@ impl<'a> From<Cow<'a, str>> for Value {
     }
 }
 
+impl From<ByteString> for Value {
+    /// Convert `ByteString` to `Value::String`, without copying it.
+    fn from(f: ByteString) -> Self {
+        Value::String(f)
+    }
+}
+
 impl From<Number> for Value {
     /// Convert `Number` to `Value`
     ///
@@ -169,6 +171,13 @@ impl From<Map<ByteString, Value>> for Value {
     }
 }
 
+impl From<RawBytesValue> for Value {
+    /// Convert `RawBytesValue` to `Value::Raw`
+    fn from(f: RawBytesValue) -> Self {
+        Value::Raw(f)
+    }
+}
+
 impl<T: Into<Value>> From<Vec<T>> for Value {
     /// Convert a `Vec` to `Value`
     ///
@@ -1,4 +1,4 @@
-use super::Value;
+use super::{LazyValue, Value};
 use crate::lib::*;
 use crate::map::Map;
 
@@ -46,6 +46,10 @@ pub trait Index: private::Sealed {
     /// object.
     #[doc(hidden)]
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value;
+
+    /// Return None if the key is not in the array or object.
+    #[doc(hidden)]
+    fn index_into_lazy(&self, v: &LazyValue) -> Option<LazyValue>;
 }
 
 impl Index for usize {
@@ -72,6 +76,9 @@ impl Index for usize {
             _ => panic!("cannot access index {} of JSON {}", self, Type(v)),
         }
     }
+    fn index_into_lazy(&self, v: &LazyValue) -> Option<LazyValue> {
+        v.element(*self)
+    }
 }
 
 impl Index for str {
@@ -96,6 +103,9 @@ impl Index for str {
             _ => panic!("cannot access key {:?} in JSON {}", self, Type(v)),
         }
     }
+    fn index_into_lazy(&self, v: &LazyValue) -> Option<LazyValue> {
+        v.field(self)
+    }
 }
 
 impl Index for String {
@@ -108,6 +118,9 @@ impl Index for String {
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         self[..].index_or_insert(v)
     }
+    fn index_into_lazy(&self, v: &LazyValue) -> Option<LazyValue> {
+        self[..].index_into_lazy(v)
+    }
 }
 
 impl<T> Index for &T
@@ -123,6 +136,9 @@ where
     fn index_or_insert<'v>(&self, v: &'v mut Value) -> &'v mut Value {
         (**self).index_or_insert(v)
     }
+    fn index_into_lazy(&self, v: &LazyValue) -> Option<LazyValue> {
+        (**self).index_into_lazy(v)
+    }
 }
 
 // Prevent users from implementing the Index trait.
@@ -146,6 +162,7 @@ impl<'a> fmt::Display for Type<'a> {
             Value::String(_) => formatter.write_str("string"),
             Value::Array(_) => formatter.write_str("array"),
             Value::Object(_) => formatter.write_str("object"),
+            Value::Raw(_) => formatter.write_str("raw value"),
         }
     }
 }
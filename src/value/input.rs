@@ -0,0 +1,333 @@
+//! A deserializer wrapper that keeps track of the input buffer, so that the
+//! `ByteString`, `Value` and `RawBytesValue` found anywhere in a typed
+//! document reference it, for [`from_bytes`](super::from_bytes).
+//!
+//! Every deserializer, visitor and access the wrapped deserializer hands out
+//! is wrapped in turn. When the visitor of one of these types asks for a
+//! value, the matching seed runs on the wrapped deserializer and the result
+//! is handed over to the visitor.
+
+use crate::lib::*;
+use crate::value::{raw, shared, ByteStringSeed, BytesSeed, RawBytesSeed, Value};
+use bytes::Bytes;
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+pub(super) struct Input<'b, T> {
+    inner: T,
+    bytes: &'b Bytes,
+}
+
+impl<'b, T> Input<'b, T> {
+    /// `bytes` must be the input of the serde_json deserializer `inner`.
+    pub(super) fn new(inner: T, bytes: &'b Bytes) -> Self {
+        Input { inner, bytes }
+    }
+
+    fn wrap<U>(&self, inner: U) -> Input<'b, U> {
+        Input::new(inner, self.bytes)
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let visitor = self.wrap(visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 'b, D> Deserializer<'de> for Input<'b, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == raw::TOKEN {
+            let raw = tri!(RawBytesSeed::new(self.bytes).deserialize(self.inner));
+            return shared::hand_over(Value::Raw(raw), visitor);
+        }
+        let visitor = self.wrap(visitor);
+        self.inner.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if shared::takes_value::<V>() {
+            let value = tri!(BytesSeed::new(self.bytes).deserialize(self.inner));
+            return shared::hand_over(value, visitor);
+        }
+        let visitor = self.wrap(visitor);
+        self.inner.deserialize_any(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if shared::takes_string::<V>() {
+            let string = tri!(ByteStringSeed::new(self.bytes).deserialize(self.inner));
+            return shared::hand_over(Value::String(string), visitor);
+        }
+        let visitor = self.wrap(visitor);
+        self.inner.deserialize_str(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<E>(self, $($arg: $ty),*) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<'de, 'b, V> Visitor<'de> for Input<'b, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(v: bool)
+        visit_i8(v: i8)
+        visit_i16(v: i16)
+        visit_i32(v: i32)
+        visit_i64(v: i64)
+        visit_i128(v: i128)
+        visit_u8(v: u8)
+        visit_u16(v: u16)
+        visit_u32(v: u32)
+        visit_u64(v: u64)
+        visit_u128(v: u128)
+        visit_f32(v: f32)
+        visit_f64(v: f64)
+        visit_char(v: char)
+        visit_str(v: &str)
+        visit_borrowed_str(v: &'de str)
+        visit_string(v: String)
+        visit_bytes(v: &[u8])
+        visit_borrowed_bytes(v: &'de [u8])
+        visit_byte_buf(v: Vec<u8>)
+        visit_none()
+        visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let seq = self.wrap(seq);
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let map = self.wrap(map);
+        self.inner.visit_map(map)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let data = self.wrap(data);
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<'de, 'b, T> DeserializeSeed<'de> for Input<'b, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let deserializer = self.wrap(deserializer);
+        self.inner.deserialize(deserializer)
+    }
+}
+
+impl<'de, 'b, A> SeqAccess<'de> for Input<'b, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.next_element_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'b, A> MapAccess<'de> for Input<'b, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'b, A> EnumAccess<'de> for Input<'b, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Input<'b, A::Variant>;
+
+    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, Self::Variant), A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        let (value, variant) = tri!(self.inner.variant_seed(seed));
+        Ok((value, Input::new(variant, self.bytes)))
+    }
+}
+
+impl<'de, 'b, A> VariantAccess<'de> for Input<'b, A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let seed = self.wrap(seed);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = self.wrap(visitor);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let visitor = self.wrap(visitor);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
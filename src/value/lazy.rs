@@ -0,0 +1,360 @@
+use super::parse_index;
+use crate::lib::*;
+use crate::parser::unescape;
+use crate::path::JsonPathInst;
+use crate::value::{Index, RawBytesValue, Value};
+use bytes::Bytes;
+use serde::ser::{Serialize, Serializer};
+use serde_json::Error;
+
+/// A JSON document that is parsed on demand.
+///
+/// The document is validated when the `LazyValue` is created, but no tree is
+/// built. Looking up a value with [`get`](LazyValue::get),
+/// [`pointer`](LazyValue::pointer) or [`select`](LazyValue::select) scans
+/// the text to find it, and returns a `LazyValue` referencing a slice of the
+/// same buffer. Only [`to_value`](LazyValue::to_value) builds a [`Value`].
+///
+/// Parts of the document that are not accessed stay as they were in the
+/// source: they are serialized back unchanged, and can be stored in a
+/// `Value` tree as [`Value::Raw`].
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::value::LazyValue;
+/// let doc = LazyValue::from_bytes(Bytes::from_static(
+///     br#"{"data": {"__typename": "Query", "items": [1, 2, 3]}}"#,
+/// ))?;
+///
+/// assert_eq!(doc.pointer("/data/__typename").unwrap().to_value()?, "Query");
+/// let items = doc.get("data").and_then(|data| data.get("items")).unwrap();
+/// assert_eq!(items.get(1).unwrap().to_value()?, 2);
+/// assert_eq!(items.to_string(), "[1, 2, 3]");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct LazyValue {
+    raw: RawBytesValue,
+}
+
+impl LazyValue {
+    /// Validates `bytes` as a JSON document without building a tree.
+    pub fn from_bytes(bytes: Bytes) -> Result<Self, Error> {
+        RawBytesValue::from_bytes(bytes).map(LazyValue::from_raw)
+    }
+
+    /// `raw` must be a non-empty, valid JSON value without surrounding
+    /// whitespace, which the scanners rely on.
+    pub(crate) fn from_raw(raw: RawBytesValue) -> Self {
+        LazyValue { raw }
+    }
+
+    /// Returns the source text of this value.
+    pub fn raw(&self) -> &RawBytesValue {
+        &self.raw
+    }
+
+    /// Index into an object with a string key, or into an array with an
+    /// integer. Returns `None` if the key or index does not exist or if the
+    /// type does not match.
+    ///
+    /// As with [`Value::from_bytes`], the last entry wins if an object has
+    /// duplicate keys.
+    pub fn get<I: Index>(&self, index: I) -> Option<LazyValue> {
+        index.index_into_lazy(self)
+    }
+
+    /// Looks up a value by a JSON Pointer, like [`Value::pointer`].
+    pub fn pointer(&self, pointer: &str) -> Option<LazyValue> {
+        if pointer.is_empty() {
+            return Some(self.clone());
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+        let mut target = self.clone();
+        for token in pointer.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            target = match target.first_byte() {
+                b'{' => target.field(&token)?,
+                b'[' => parse_index(&token).and_then(|i| target.element(i))?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Selects values with a JSONPath expression.
+    ///
+    /// The leading keys and indexes of the path are looked up lazily, then the
+    /// value they lead to is parsed to evaluate the rest of the path. Filter
+    /// expressions can refer to the root of the document, so paths containing
+    /// filters after that prefix parse the whole document.
+    pub fn select(&self, path: &JsonPathInst) -> Vec<Value> {
+        path.select_lazy(self)
+    }
+
+    /// Parses this value into a [`Value`]. Strings reference the same buffer.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        self.raw.to_value()
+    }
+
+    fn first_byte(&self) -> u8 {
+        self.raw.inner()[0]
+    }
+
+    fn slice(&self, range: ops::Range<usize>) -> LazyValue {
+        LazyValue::from_raw(self.raw.slice(range))
+    }
+
+    pub(crate) fn field(&self, key: &str) -> Option<LazyValue> {
+        let bytes = self.raw.inner();
+        if bytes[0] != b'{' {
+            return None;
+        }
+
+        let mut found = None;
+        let mut i = skip_whitespace(bytes, 1);
+        if bytes[i] == b'}' {
+            return None;
+        }
+        loop {
+            let key_end = skip_string(bytes, i);
+            let raw_key = &bytes[i + 1..key_end - 1];
+            // skip the colon
+            let start = skip_whitespace(bytes, skip_whitespace(bytes, key_end) + 1);
+            let end = skip_value(bytes, start);
+            if key_matches(raw_key, key) {
+                found = Some(start..end);
+            }
+            i = skip_whitespace(bytes, end);
+            if bytes[i] != b',' {
+                return found.map(|range| self.slice(range));
+            }
+            i = skip_whitespace(bytes, i + 1);
+        }
+    }
+
+    pub(crate) fn element(&self, index: usize) -> Option<LazyValue> {
+        let bytes = self.raw.inner();
+        if bytes[0] != b'[' {
+            return None;
+        }
+
+        let mut i = skip_whitespace(bytes, 1);
+        if bytes[i] == b']' {
+            return None;
+        }
+        for _ in 0..index {
+            i = skip_whitespace(bytes, skip_value(bytes, i));
+            if bytes[i] != b',' {
+                return None;
+            }
+            i = skip_whitespace(bytes, i + 1);
+        }
+        Some(self.slice(i..skip_value(bytes, i)))
+    }
+}
+
+impl From<LazyValue> for Value {
+    /// Keeps the source text as a [`Value::Raw`].
+    fn from(lazy: LazyValue) -> Self {
+        Value::Raw(lazy.raw)
+    }
+}
+
+impl Debug for LazyValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_tuple("LazyValue")
+            .field(&format_args!("{}", self.raw))
+            .finish()
+    }
+}
+
+impl Display for LazyValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.raw, f)
+    }
+}
+
+impl Serialize for LazyValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.raw.serialize(serializer)
+    }
+}
+
+// The functions below scan text that was already validated, and do not check
+// for syntax errors.
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while let Some(b' ' | b'\n' | b'\t' | b'\r') = bytes.get(i) {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the index following the string starting at `bytes[start]`.
+fn skip_string(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    loop {
+        match bytes[i] {
+            b'"' => return i + 1,
+            b'\\' => i += 2,
+            _ => i += 1,
+        }
+    }
+}
+
+/// Returns the index following the value starting at `bytes[start]`.
+fn skip_value(bytes: &[u8], start: usize) -> usize {
+    match bytes[start] {
+        b'"' => skip_string(bytes, start),
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut i = start;
+            loop {
+                match bytes[i] {
+                    b'"' => {
+                        i = skip_string(bytes, i);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return i + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+        _ => {
+            let mut i = start;
+            while !matches!(
+                bytes.get(i),
+                None | Some(b',' | b'}' | b']' | b' ' | b'\n' | b'\t' | b'\r')
+            ) {
+                i += 1;
+            }
+            i
+        }
+    }
+}
+
+fn key_matches(raw: &[u8], key: &str) -> bool {
+    if !raw.contains(&b'\\') {
+        return raw == key.as_bytes();
+    }
+    let mut unescaped = Vec::with_capacity(raw.len());
+    unescape(raw, &mut unescaped, false).is_ok() && unescaped == key.as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LazyValue;
+    use crate::path::JsonPathInst;
+    use crate::{json, Value};
+    use bytes::Bytes;
+
+    const DOCUMENT: &[u8] = br#" {
+        "data": {
+            "__typename": "Query",
+            "list": [ {"a": 1}, "two", [3], null, -4.5e1 ],
+            "escaped\nkey": true,
+            "dup": 1,
+            "dup": 2,
+            "empty": {},
+            "nested": {"b": {"c": "d"}}
+        }
+    } "#;
+
+    fn document() -> (Bytes, LazyValue) {
+        let bytes = Bytes::from_static(DOCUMENT);
+        (bytes.clone(), LazyValue::from_bytes(bytes).unwrap())
+    }
+
+    #[test]
+    fn lookups() {
+        let (bytes, doc) = document();
+        let expected = Value::from_bytes(bytes.clone()).unwrap();
+
+        let data = doc.get("data").unwrap();
+        assert!(bytes.as_ptr_range().contains(&data.raw().inner().as_ptr()));
+        assert_eq!(data.to_value().unwrap(), expected["data"]);
+
+        for invalid in [&b""[..], b"  ", b"{\"a\":", b"[1] [2]"] {
+            assert!(LazyValue::from_bytes(Bytes::from_static(invalid)).is_err());
+        }
+
+        for pointer in [
+            "",
+            "/data/__typename",
+            "/data/list/0/a",
+            "/data/list/1",
+            "/data/list/2/0",
+            "/data/list/3",
+            "/data/list/4",
+            "/data/escaped\nkey",
+            "/data/dup",
+            "/data/empty",
+            "/data/nested/b/c",
+        ] {
+            let found = doc.pointer(pointer).unwrap().to_value().unwrap();
+            assert_eq!(&found, expected.pointer(pointer).unwrap(), "{pointer}");
+        }
+
+        for pointer in [
+            "/data/list/5",
+            "/data/missing",
+            "/data/empty/a",
+            "/data/__typename/a",
+            "data",
+        ] {
+            assert!(doc.pointer(pointer).is_none(), "{pointer}");
+        }
+        let data = doc.get("data").unwrap();
+        assert!(data.get(0).is_none());
+        assert!(data.get("list").unwrap().get("a").is_none());
+    }
+
+    #[test]
+    fn untouched_subtrees() {
+        let (_, doc) = document();
+        let list = doc.pointer("/data/list").unwrap();
+        assert_eq!(
+            list.to_string(),
+            r#"[ {"a": 1}, "two", [3], null, -4.5e1 ]"#
+        );
+        let mut value = json!({ "list": null });
+        value["list"] = list.into();
+        assert_eq!(
+            value.to_bytes(),
+            r#"{"list":[ {"a": 1}, "two", [3], null, -4.5e1 ]}"#
+        );
+    }
+
+    #[test]
+    fn json_path() {
+        let (bytes, doc) = document();
+        let expected = Value::from_bytes(bytes).unwrap();
+        for path in [
+            "$.data.__typename",
+            "$.data.list[2][0]",
+            "$.data.list[*]",
+            "$.data.nested..c",
+            "$.data.list[?(@.a == 1)]",
+            "$.data.missing.a",
+        ] {
+            let inst = JsonPathInst::new(path).unwrap();
+            let found = doc.select(&inst);
+            let selected: Vec<Value> = inst.select(&expected).map(|v| v.into_owned()).collect();
+            assert_eq!(found, selected, "{path}");
+        }
+    }
+}
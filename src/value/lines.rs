@@ -0,0 +1,361 @@
+use crate::lib::*;
+use crate::{ParseOptions, Value, ValueParser};
+use bytes::{BufMut, Bytes, BytesMut};
+use serde_json::Error;
+use std::collections::VecDeque;
+
+/// A reader of newline-delimited JSON (NDJSON or JSON Lines), returning one
+/// [`Value`] per line.
+///
+/// The input is given as a single buffer with [`JsonLines::from_bytes`], or
+/// chunk by chunk with [`feed`](JsonLines::feed) as it arrives. Strings
+/// reference the input, except those of a line split across two chunks, which
+/// is copied.
+///
+/// Lines containing only whitespace are skipped, and a line that fails to
+/// parse is reported as an error, with its line number, without stopping the
+/// iteration.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, JsonLines};
+/// let input = Bytes::from_static(b"{\"id\": 1}\n{\"id\": 2}\n{\"id\": \n{\"id\": 4}\n");
+/// let mut lines = JsonLines::from_bytes(input);
+///
+/// assert_eq!(lines.next().unwrap()?, json!({"id": 1}));
+/// assert_eq!(lines.next().unwrap()?, json!({"id": 2}));
+/// assert_eq!(
+///     lines.next().unwrap().unwrap_err().to_string(),
+///     "EOF while parsing a value at line 3 column 7",
+/// );
+/// assert_eq!(lines.next().unwrap()?, json!({"id": 4}));
+/// assert!(lines.next().is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+///
+/// When the input arrives in chunks, the iterator returns `None` once the
+/// complete lines are consumed, and can be resumed after the next chunk.
+/// Chunks are queued without copying, even when they are fed before the lines
+/// of the previous ones are read. The last line does not need to end with a
+/// newline, after [`finish`](JsonLines::finish) is called.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, JsonLines};
+/// let mut lines = JsonLines::new();
+/// lines.feed(Bytes::from_static(b"[1]\n[2"));
+/// assert_eq!(lines.next().unwrap()?, json!([1]));
+/// assert!(lines.next().is_none());
+///
+/// lines.feed(Bytes::from_static(b", 3]\n[4]"));
+/// assert_eq!(lines.next().unwrap()?, json!([2, 3]));
+/// assert!(lines.next().is_none());
+///
+/// lines.finish();
+/// assert_eq!(lines.next().unwrap()?, json!([4]));
+/// assert!(lines.next().is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub struct JsonLines {
+    splitter: Splitter,
+    options: ParseOptions,
+    /// Number of lines returned by the splitter.
+    line: usize,
+}
+
+impl JsonLines {
+    /// Creates a reader waiting for input.
+    pub fn new() -> Self {
+        JsonLines::with_options(ParseOptions::new())
+    }
+
+    /// Creates a reader parsing each line with `options`. The limits apply
+    /// to each line separately.
+    pub fn with_options(options: ParseOptions) -> Self {
+        JsonLines {
+            splitter: Splitter::new(b'\n'),
+            options,
+            line: 0,
+        }
+    }
+
+    /// Creates a reader over a complete input.
+    pub fn from_bytes(input: Bytes) -> Self {
+        let mut lines = JsonLines::new();
+        lines.feed(input);
+        lines.finish();
+        lines
+    }
+
+    /// Adds the next chunk of input.
+    pub fn feed(&mut self, chunk: Bytes) {
+        self.splitter.feed(chunk);
+    }
+
+    /// Marks the end of the input, so that the last line is returned even if
+    /// it does not end with a newline.
+    pub fn finish(&mut self) {
+        self.splitter.finish();
+    }
+}
+
+impl Default for JsonLines {
+    fn default() -> Self {
+        JsonLines::new()
+    }
+}
+
+impl Iterator for JsonLines {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.splitter.next()?;
+            self.line += 1;
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            return Some(parse_record(line, &self.options, self.line, 1));
+        }
+    }
+}
+
+/// Parses a single document starting at `line` and `column` of the whole
+/// input.
+pub(super) fn parse_record(
+    record: Bytes,
+    options: &ParseOptions,
+    line: usize,
+    column: usize,
+) -> Result<Value, Error> {
+    let mut parser = ValueParser::with_options(options.clone()).start_at(line, column);
+    tri!(parser.feed(record));
+    parser.finish()
+}
+
+/// A writer of newline-delimited JSON, appending each [`Value`] to a buffer
+/// as compact JSON followed by a newline.
+///
+/// ```
+/// # use serde_json_bytes::{json, JsonLinesWriter};
+/// let mut writer = JsonLinesWriter::new();
+/// writer.write(&json!({"id": 1}));
+/// writer.write(&json!("two"));
+///
+/// assert_eq!(writer.split(), "{\"id\":1}\n\"two\"\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonLinesWriter {
+    buf: BytesMut,
+}
+
+impl JsonLinesWriter {
+    pub fn new() -> Self {
+        JsonLinesWriter::default()
+    }
+
+    /// Appends to an existing buffer.
+    pub fn with_buffer(buf: BytesMut) -> Self {
+        JsonLinesWriter { buf }
+    }
+
+    /// Appends `value` as a line.
+    ///
+    /// Raw values are written as they were parsed, so the newlines they
+    /// contain between tokens are replaced with spaces.
+    pub fn write(&mut self, value: &Value) {
+        let start = self.buf.len();
+        value.to_bytes_into(&mut self.buf);
+        for byte in &mut self.buf[start..] {
+            // strings cannot contain unescaped newlines
+            if *byte == b'\n' {
+                *byte = b' ';
+            }
+        }
+        self.buf.put_u8(b'\n');
+    }
+
+    /// Takes the lines written so far, leaving the writer empty.
+    pub fn split(&mut self) -> Bytes {
+        self.buf.split().freeze()
+    }
+
+    /// Returns the buffer.
+    pub fn into_inner(self) -> BytesMut {
+        self.buf
+    }
+}
+
+/// Splits chunks of input on a delimiter byte, returning slices of the
+/// chunks for the records they contain entirely.
+///
+/// Chunks are queued as they are fed, and only the start of a record that
+/// continues in the next chunk is copied.
+pub(super) struct Splitter {
+    delimiter: u8,
+    chunks: VecDeque<Bytes>,
+    /// Start of a record split across chunks.
+    partial: BytesMut,
+    eof: bool,
+}
+
+impl Splitter {
+    pub(super) fn new(delimiter: u8) -> Self {
+        Splitter {
+            delimiter,
+            chunks: VecDeque::new(),
+            partial: BytesMut::new(),
+            eof: false,
+        }
+    }
+
+    pub(super) fn feed(&mut self, chunk: Bytes) {
+        debug_assert!(!self.eof, "input fed after the end");
+        if !chunk.is_empty() {
+            self.chunks.push_back(chunk);
+        }
+    }
+
+    pub(super) fn finish(&mut self) {
+        self.eof = true;
+    }
+
+    /// Returns the next record, without its delimiter, or `None` if more
+    /// input is needed or the input is consumed.
+    pub(super) fn next(&mut self) -> Option<Bytes> {
+        while let Some(chunk) = self.chunks.front_mut() {
+            if let Some(i) = chunk.iter().position(|&b| b == self.delimiter) {
+                let record = chunk.split_to(i + 1).slice(..i);
+                if chunk.is_empty() {
+                    self.chunks.pop_front();
+                }
+                return Some(self.join(record));
+            }
+
+            let chunk = self.chunks.pop_front().expect("the chunk was just read");
+            if self.eof && self.chunks.is_empty() {
+                return Some(self.join(chunk));
+            }
+            // the record continues in the next chunk, `partial` never
+            // contains a delimiter
+            self.partial.extend_from_slice(&chunk);
+        }
+
+        if self.eof && !self.partial.is_empty() {
+            return Some(self.partial.split().freeze());
+        }
+        None
+    }
+
+    /// Prepends the start of the record from the previous chunks, if any.
+    fn join(&mut self, record: Bytes) -> Bytes {
+        if self.partial.is_empty() {
+            record
+        } else {
+            self.partial.extend_from_slice(&record);
+            self.partial.split().freeze()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonLines, JsonLinesWriter};
+    use crate::value::LazyValue;
+    use crate::{json, DuplicateKeys, ParseOptions, Value};
+    use bytes::Bytes;
+
+    const INPUT: &str = "{\"a\": \"b\"}\r\n\n  \n[1, 2]\n\"c\"";
+
+    fn collect(lines: JsonLines) -> Vec<Result<Value, String>> {
+        lines.map(|line| line.map_err(|e| e.to_string())).collect()
+    }
+
+    #[test]
+    fn read() {
+        let input = Bytes::from_static(INPUT.as_bytes());
+        let expected = vec![Ok(json!({"a": "b"})), Ok(json!([1, 2])), Ok(json!("c"))];
+        let values = collect(JsonLines::from_bytes(input.clone()));
+        assert_eq!(values, expected);
+
+        let value = JsonLines::from_bytes(input.clone())
+            .next()
+            .unwrap()
+            .unwrap();
+        let b = value["a"].as_str().unwrap();
+        assert!(input.as_ptr_range().contains(&b.as_ptr()));
+
+        for i in 0..=INPUT.len() {
+            let mut lines = JsonLines::new();
+            let mut values = Vec::new();
+            lines.feed(input.slice(..i));
+            values.extend(&mut lines);
+            lines.feed(input.slice(i..));
+            values.extend(&mut lines);
+            lines.finish();
+            values.extend(&mut lines);
+            let values: Vec<_> = values.into_iter().map(Result::unwrap).collect();
+            assert_eq!(
+                values,
+                [json!({"a": "b"}), json!([1, 2]), json!("c")],
+                "split at {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn queued_chunks() {
+        let first = Bytes::from_static(b"\"a string long enough not to be inline\"\n[\"");
+        let second =
+            Bytes::from_static(b"b\"]\n\"another string not short enough to be inline\"\n");
+        let mut lines = JsonLines::new();
+        lines.feed(first.clone());
+        lines.feed(second.clone());
+        let values: Vec<Value> = lines.map(Result::unwrap).collect();
+
+        assert_eq!(values.len(), 3);
+        let in_chunk = |value: &Value, chunk: &Bytes| {
+            let s = value.as_str().unwrap();
+            chunk.as_ptr_range().contains(&s.as_ptr())
+        };
+        assert!(in_chunk(&values[0], &first));
+        assert_eq!(values[1], json!(["b"]));
+        assert!(in_chunk(&values[2], &second));
+    }
+
+    #[test]
+    fn errors() {
+        let input = Bytes::from_static(b"1\n\n[1,\n2 3\n{\"a\": 1, \"a\": 2}\ntrue");
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Reject);
+        let mut lines = JsonLines::with_options(options);
+        lines.feed(input);
+        lines.finish();
+        assert_eq!(
+            collect(lines),
+            [
+                Ok(json!(1)),
+                Err("EOF while parsing a value at line 3 column 3".to_owned()),
+                Err("trailing characters at line 4 column 3".to_owned()),
+                Err("duplicate key \"a\" in the root object at line 5 column 12".to_owned()),
+                Ok(json!(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn write() {
+        let raw = LazyValue::from_bytes(Bytes::from_static(b"[\n  \"x\\ny\"\n]")).unwrap();
+        let mut writer = JsonLinesWriter::new();
+        writer.write(&json!({"text": "line\nbreak"}));
+        writer.write(&raw.into());
+        let output = writer.split();
+        assert_eq!(output, "{\"text\":\"line\\nbreak\"}\n[   \"x\\ny\" ]\n");
+
+        let values = collect(JsonLines::from_bytes(output));
+        assert_eq!(
+            values,
+            [Ok(json!({"text": "line\nbreak"})), Ok(json!(["x\ny"]))]
+        );
+        assert!(writer.into_inner().is_empty());
+    }
+}
@@ -96,15 +96,25 @@ use serde::ser::Serialize;
 use serde_json::error::Error;
 use serde_json::Number;
 
-pub use self::bytes::{ByteStringSeed, BytesSeed};
+pub use self::bytes::{from_bytes, ByteStringSeed, BytesSeed};
+pub use self::compact::BufferUsage;
+pub use self::elements::ArrayElements;
+pub use self::events::{Event, EventReader};
 pub use self::index::Index;
+pub use self::lazy::LazyValue;
+pub use self::lines::{JsonLines, JsonLinesWriter};
+pub use self::options::{DuplicateKeys, LimitExceeded, ParseOptions};
+pub use self::raw::{RawBytesSeed, RawBytesValue};
+pub use self::seq::{JsonSeq, JsonSeqWriter};
 pub use self::ser::Serializer;
+pub use self::stream::ValueParser;
+pub use self::writer::JsonWriter;
 pub use crate::map::Map;
 
 /// Represents any valid JSON value.
 ///
 /// See the [`serde_json::value` module documentation](self) for usage examples.
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq)]
 pub enum Value {
     /// Represents a JSON null value.
     ///
@@ -165,6 +175,27 @@ pub enum Value {
     /// let v = json!({ "an": "object" });
     /// ```
     Object(Map<ByteString, Value>),
+
+    /// Represents JSON text that is kept as is, without being parsed into a
+    /// tree. It is serialized verbatim.
+    ///
+    /// A raw value is opaque: accessors like [`Value::as_str`] or indexing do
+    /// not look into it. Comparing or hashing it parses it, so it is equal to
+    /// the value it contains, like it is when converted to a
+    /// `serde_json::Value`.
+    ///
+    /// The text is parsed again on every comparison or hash, and the tree is
+    /// dropped afterwards. A value used as a key of a `HashMap` or
+    /// `HashSet` should not contain raw values: parse them once with
+    /// [`RawBytesValue::to_value`] instead.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::value::{RawBytesValue, Value};
+    /// #
+    /// let v = Value::Raw(RawBytesValue::from_bytes(Bytes::from_static(b"[1, 2]")).unwrap());
+    /// ```
+    Raw(RawBytesValue),
 }
 
 impl Debug for Value {
@@ -184,6 +215,29 @@ impl Debug for Value {
                 Debug::fmt(v, formatter)?;
                 formatter.write_str(")")
             }
+            Value::Raw(ref v) => Debug::fmt(v, formatter),
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Raw(raw) => match raw.to_value() {
+                Ok(value) => value.hash(state),
+                Err(_) => raw.hash(state),
+            },
+            _ => {
+                mem::discriminant(self).hash(state);
+                match self {
+                    Value::Null | Value::Raw(_) => {}
+                    Value::Bool(b) => b.hash(state),
+                    Value::Number(n) => n.hash(state),
+                    Value::String(s) => s.hash(state),
+                    Value::Array(v) => v.hash(state),
+                    Value::Object(o) => o.hash(state),
+                }
+            }
         }
     }
 }
@@ -720,6 +774,20 @@ impl Value {
         }
     }
 
+    /// Returns true if the `Value` is a raw value. Returns false otherwise.
+    pub const fn is_raw(&self) -> bool {
+        self.as_raw().is_some()
+    }
+
+    /// If the `Value` is a raw value, returns the associated
+    /// [`RawBytesValue`]. Returns None otherwise.
+    pub const fn as_raw(&self) -> Option<&RawBytesValue> {
+        match *self {
+            Value::Raw(ref raw) => Some(raw),
+            _ => None,
+        }
+    }
+
     /// Looks up a value by a JSON Pointer.
     ///
     /// JSON Pointer defines a string syntax for identifying a specific value
@@ -895,11 +963,24 @@ impl Default for Value {
 }
 
 mod bytes;
+mod compact;
 mod de;
+mod elements;
+pub(crate) mod encode;
+mod events;
 mod from;
 mod index;
+mod input;
+mod lazy;
+mod lines;
+mod options;
 mod partial_eq;
+mod raw;
+mod seq;
 mod ser;
+pub(crate) mod shared;
+mod stream;
+mod writer;
 
 /// Convert a `T` into `serde_json::Value` which is an enum that can represent
 /// any valid JSON data.
@@ -1008,7 +1089,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::json;
+    use crate::{json, Map};
+    use ::bytes::Bytes;
+    use serde::{Deserialize, Serialize};
 
     use super::*;
 
@@ -1021,4 +1104,106 @@ mod tests {
 
         assert_eq!(val.to_string(), second_val.to_string());
     }
+
+    #[test]
+    fn shared_buffers() {
+        #[derive(Deserialize, Serialize)]
+        struct Request {
+            query: ByteString,
+            variables: Map<ByteString, Value>,
+            extensions: Value,
+        }
+
+        let value = json!({
+            "query": "a string long enough not to be stored inline",
+            "variables": {"a key long enough not to be stored inline": "v"},
+            "extensions": {"e": ["another string long enough not to be inline"]},
+        });
+        let same = |a: &str, b: &str| a.as_ptr() == b.as_ptr();
+        let query = value["query"].as_str().unwrap();
+        let (key, _) = value["variables"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap();
+        let element = value["extensions"]["e"][0].as_str().unwrap();
+
+        let request: Request = from_value(value.clone()).unwrap();
+        assert!(same(&request.query, query));
+        assert!(same(request.variables.keys().next().unwrap(), key));
+        assert!(same(request.extensions["e"][0].as_str().unwrap(), element));
+
+        let borrowed = Request::deserialize(&value).unwrap();
+        assert!(same(&borrowed.query, query));
+        assert!(same(borrowed.variables.keys().next().unwrap(), key));
+
+        let back = to_value(&request).unwrap();
+        assert_eq!(back, value);
+        assert!(same(back["query"].as_str().unwrap(), query));
+        let (back_key, _) = back["variables"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap();
+        assert!(same(back_key, key));
+        assert!(same(back["extensions"]["e"][0].as_str().unwrap(), element));
+
+        let raw = RawBytesValue::from_bytes(Bytes::from_static(b"[1, 2]")).unwrap();
+        match to_value(&raw).unwrap() {
+            Value::Raw(back) => assert_eq!(back.inner().as_ptr(), raw.inner().as_ptr()),
+            other => panic!("expected a raw value, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn owned_strings() {
+        // visitors of other types still take the strings of an owned value
+        struct Owned {
+            string: String,
+            moved: bool,
+        }
+
+        impl<'de> Deserialize<'de> for Owned {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct OwnedVisitor;
+
+                impl serde::de::Visitor<'_> for OwnedVisitor {
+                    type Value = Owned;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str("a string")
+                    }
+
+                    fn visit_str<E>(self, v: &str) -> Result<Owned, E> {
+                        let string = v.to_owned();
+                        Ok(Owned {
+                            string,
+                            moved: false,
+                        })
+                    }
+
+                    fn visit_string<E>(self, string: String) -> Result<Owned, E> {
+                        Ok(Owned {
+                            string,
+                            moved: true,
+                        })
+                    }
+                }
+
+                deserializer.deserialize_string(OwnedVisitor)
+            }
+        }
+
+        let owned: Owned = from_value(json!("s")).unwrap();
+        assert!(owned.moved);
+        assert_eq!(owned.string, "s");
+        let owned: Vec<Owned> = from_value(json!(["a", "b"])).unwrap();
+        assert!(owned[1].moved);
+        assert_eq!(owned[1].string, "b");
+    }
 }
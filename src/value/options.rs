@@ -0,0 +1,295 @@
+use crate::KeyInterner;
+use std::fmt;
+use std::sync::Arc;
+
+/// Options for [`Value::from_bytes_with_options`] and
+/// [`ValueParser::with_options`]. The limits, the duplicate key policy and the
+/// key interner also apply to [`BytesSeed::with_options`].
+///
+/// [`Value::from_bytes_with_options`]: crate::Value::from_bytes_with_options
+/// [`ValueParser::with_options`]: crate::ValueParser::with_options
+/// [`BytesSeed::with_options`]: crate::value::BytesSeed::with_options
+///
+/// The limits are checked while parsing, so an input going over one of them
+/// is rejected before it is fully parsed. The error message names the limit
+/// that was exceeded, and [`LimitExceeded::from_error`] returns it.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{ParseOptions, Value};
+/// let options = ParseOptions::new().defer_unescape(true);
+/// let value = Value::from_bytes_with_options(
+///     Bytes::from_static(br#"{"text": "line\nbreak"}"#),
+///     &options,
+/// )?;
+///
+/// assert_eq!(value["text"], "line\nbreak");
+/// assert_eq!(value.to_bytes(), r#"{"text":"line\nbreak"}"#);
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    pub(crate) defer_unescape: bool,
+    pub(crate) arena: bool,
+    pub(crate) key_interner: Option<Arc<KeyInterner>>,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_string_length: Option<usize>,
+    pub(crate) max_object_keys: Option<usize>,
+    pub(crate) max_array_length: Option<usize>,
+    pub(crate) max_nodes: Option<usize>,
+    pub(crate) max_size: Option<usize>,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) lenient: bool,
+}
+
+impl ParseOptions {
+    /// Returns the default options, which parse standard JSON like
+    /// [`Value::from_bytes`](crate::Value::from_bytes).
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    /// Keeps string values containing escape sequences as slices of the
+    /// input. They are decoded on the first call to
+    /// [`ByteString::as_str`](crate::ByteString::as_str), and written back
+    /// verbatim by [`Value::to_bytes`](crate::Value::to_bytes).
+    ///
+    /// Escape sequences are still validated while parsing. Object keys are
+    /// always decoded, since the map hashes or compares them as soon as they
+    /// are inserted, and so are strings split across chunks, which are copied
+    /// anyway.
+    pub fn defer_unescape(mut self, defer: bool) -> Self {
+        self.defer_unescape = defer;
+        self
+    }
+
+    /// Copies the strings that cannot reference the input, because they
+    /// contain escape sequences or are split across chunks, into shared
+    /// buffers instead of allocating each of them separately.
+    ///
+    /// This reduces allocations for documents with many escaped strings or
+    /// keys, but a string keeps the whole buffer it was copied into alive.
+    /// Short strings are stored inline in any case.
+    pub fn arena(mut self, arena: bool) -> Self {
+        self.arena = arena;
+        self
+    }
+
+    /// Takes the object keys from `interner`, so that a key repeated in many
+    /// objects is stored once, and keys with escape sequences are not decoded
+    /// into a new allocation at every occurrence.
+    ///
+    /// The interner can be shared by several parses, like the lines of a
+    /// [`JsonLines`](crate::JsonLines) reader.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use std::sync::Arc;
+    /// # use serde_json_bytes::{KeyInterner, ParseOptions, Value};
+    /// let interner = Arc::new(KeyInterner::new());
+    /// let options = ParseOptions::new().key_interner(interner.clone());
+    /// let value = Value::from_bytes_with_options(
+    ///     Bytes::from_static(br#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]"#),
+    ///     &options,
+    /// )?;
+    ///
+    /// assert_eq!(value[1]["name"], "b");
+    /// assert_eq!(interner.len(), 2);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn key_interner(mut self, interner: Arc<KeyInterner>) -> Self {
+        self.key_interner = Some(interner);
+        self
+    }
+
+    /// Sets the maximum nesting depth of arrays and objects. Without it, the
+    /// limit is 128, like the default recursion limit of serde_json.
+    ///
+    /// The limit replaces the default one. A [`Value`](crate::Value) is
+    /// dropped, compared and serialized recursively, so a much larger limit
+    /// lets a deeply nested document overflow the stack after it is parsed.
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Sets the maximum length of strings and object keys, in bytes of the
+    /// input, escape sequences included.
+    pub fn max_string_length(mut self, max: usize) -> Self {
+        self.max_string_length = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of entries in an object.
+    pub fn max_object_keys(mut self, max: usize) -> Self {
+        self.max_object_keys = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of elements in an array.
+    pub fn max_array_length(mut self, max: usize) -> Self {
+        self.max_array_length = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of values in the document, counting every
+    /// array, object, string, number, boolean and null, but not object keys.
+    pub fn max_nodes(mut self, max: usize) -> Self {
+        self.max_nodes = Some(max);
+        self
+    }
+
+    /// Accepts the following extensions to JSON, for configuration files
+    /// and hand written documents:
+    ///
+    /// - `// line` and `/* block */` comments
+    /// - trailing commas in arrays and objects
+    /// - single quoted strings, in which `\'` is a valid escape sequence
+    /// - unquoted object keys made of ASCII letters, digits, `_` and `$`,
+    ///   not starting with a digit
+    /// - hexadecimal integers like `0x1F` or `-0x1F`
+    /// - a leading UTF-8 byte order mark
+    ///
+    /// Strings and keys without escape sequences still reference the input.
+    ///
+    /// ```
+    /// # use bytes::Bytes;
+    /// # use serde_json_bytes::{json, ParseOptions, Value};
+    /// let input = Bytes::from_static(b"{
+    ///     // retry policy
+    ///     attempts: 0x3,
+    ///     'backoff': [1, 2, 4,],
+    /// }");
+    /// let value = Value::from_bytes_with_options(input, &ParseOptions::new().lenient(true))?;
+    ///
+    /// assert_eq!(value, json!({"attempts": 3, "backoff": [1, 2, 4]}));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets what to do when an object contains the same key more than once.
+    /// The default is [`DuplicateKeys::KeepLast`], like
+    /// [`Value::from_bytes`](crate::Value::from_bytes).
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets the maximum size of the input in bytes. With a [`ValueParser`],
+    /// a chunk going over the limit is rejected before it is parsed.
+    ///
+    /// [`ValueParser`]: crate::ValueParser
+    pub fn max_size(mut self, max: usize) -> Self {
+        self.max_size = Some(max);
+        self
+    }
+}
+
+/// What to do with a key that appears more than once in an object.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the value of the first occurrence, ignore the others.
+    KeepFirst,
+    /// Keep the value of the last occurrence.
+    #[default]
+    KeepLast,
+    /// Fail with an error reporting the key and the JSON Pointer of the
+    /// object containing it.
+    Reject,
+}
+
+/// A limit of [`ParseOptions`] that an input went over, with its value.
+///
+/// Parsing fails with a [`serde_json::Error`], from which
+/// [`LimitExceeded::from_error`] gets the limit back.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{LimitExceeded, ParseOptions, Value};
+/// let options = ParseOptions::new().max_array_length(2);
+/// let err = Value::from_bytes_with_options(Bytes::from_static(b"[1, 2, 3]"), &options)
+///     .unwrap_err();
+///
+/// assert_eq!(LimitExceeded::from_error(&err), Some(LimitExceeded::ArrayLength(2)));
+/// assert_eq!(err.to_string(), "maximum array length of 2 exceeded at line 1 column 8");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// [`ParseOptions::max_depth`]
+    Depth(usize),
+    /// [`ParseOptions::max_string_length`]
+    StringLength(usize),
+    /// [`ParseOptions::max_object_keys`]
+    ObjectKeys(usize),
+    /// [`ParseOptions::max_array_length`]
+    ArrayLength(usize),
+    /// [`ParseOptions::max_nodes`]
+    Nodes(usize),
+    /// [`ParseOptions::max_size`]
+    Size(usize),
+}
+
+impl LimitExceeded {
+    /// Returns the limit `error` reports going over, or `None` if it was
+    /// returned for another reason.
+    ///
+    /// serde_json errors only carry a message, so the limit is recognized
+    /// from it.
+    pub fn from_error(error: &serde_json::Error) -> Option<Self> {
+        let message = error.to_string();
+        let code = if error.line() == 0 {
+            &message[..]
+        } else {
+            let suffix = format!(" at line {} column {}", error.line(), error.column());
+            message.strip_suffix(&suffix)?
+        };
+        let limits: [fn(usize) -> Self; 6] = [
+            LimitExceeded::Depth,
+            LimitExceeded::StringLength,
+            LimitExceeded::ObjectKeys,
+            LimitExceeded::ArrayLength,
+            LimitExceeded::Nodes,
+            LimitExceeded::Size,
+        ];
+        limits.into_iter().find_map(|limit| {
+            let (prefix, suffix) = limit(0).message();
+            let max = code.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            max.parse().ok().map(limit)
+        })
+    }
+
+    /// Returns the value of the limit.
+    pub fn max(self) -> usize {
+        match self {
+            LimitExceeded::Depth(max)
+            | LimitExceeded::StringLength(max)
+            | LimitExceeded::ObjectKeys(max)
+            | LimitExceeded::ArrayLength(max)
+            | LimitExceeded::Nodes(max)
+            | LimitExceeded::Size(max) => max,
+        }
+    }
+
+    /// Returns the error message before and after the value of the limit.
+    fn message(self) -> (&'static str, &'static str) {
+        match self {
+            LimitExceeded::Depth(_) => ("maximum depth of ", " exceeded"),
+            LimitExceeded::StringLength(_) => ("maximum string length of ", " bytes exceeded"),
+            LimitExceeded::ObjectKeys(_) => ("maximum number of object keys of ", " exceeded"),
+            LimitExceeded::ArrayLength(_) => ("maximum array length of ", " exceeded"),
+            LimitExceeded::Nodes(_) => ("maximum number of values of ", " exceeded"),
+            LimitExceeded::Size(_) => ("maximum input size of ", " bytes exceeded"),
+        }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (prefix, suffix) = self.message();
+        write!(f, "{prefix}{}{suffix}", self.max())
+    }
+}
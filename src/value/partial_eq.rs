@@ -21,6 +21,24 @@ fn eq_str(value: &Value, other: &str) -> bool {
     value.as_str() == Some(other)
 }
 
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => a == b,
+            (Value::Raw(a), Value::Raw(b)) if a == b => true,
+            (Value::Raw(raw), other) | (other, Value::Raw(raw)) => {
+                raw.to_value().is_ok_and(|raw| raw == *other)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl PartialEq<str> for Value {
     fn eq(&self, other: &str) -> bool {
         eq_str(self, other)
@@ -57,6 +75,37 @@ impl PartialEq<Value> for String {
     }
 }
 
+impl PartialEq<serde_json::Value> for Value {
+    fn eq(&self, other: &serde_json::Value) -> bool {
+        use serde_json::Value as Json;
+
+        match (self, other) {
+            (Value::Null, Json::Null) => true,
+            (Value::Bool(a), Json::Bool(b)) => a == b,
+            (Value::Number(a), Json::Number(b)) => a == b,
+            (Value::String(a), Json::String(b)) => a.as_str() == b,
+            (Value::Array(a), Json::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a == b)
+            }
+            (Value::Object(a), Json::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, v)| b.get(k.as_str()).is_some_and(|other| v == other))
+            }
+            (Value::Raw(raw), other) => {
+                serde_json::from_str::<Json>(raw.get()).is_ok_and(|raw| raw == *other)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<Value> for serde_json::Value {
+    fn eq(&self, other: &Value) -> bool {
+        other == self
+    }
+}
+
 macro_rules! partialeq_numeric {
     ($($eq:ident [$($ty:ty)*])*) => {
         $($(
@@ -93,3 +142,74 @@ partialeq_numeric! {
     eq_f64[f32 f64]
     eq_bool[bool]
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::value::LazyValue;
+    use crate::{json, Map, Value};
+    use bytes::Bytes;
+
+    #[test]
+    fn serde_json_value() {
+        let raw = LazyValue::from_bytes(Bytes::from_static(br#"{"r": [1, "\u00e9"]}"#)).unwrap();
+        let value = json!({"a": [null, true, 1.5, -2, "s"], "b": {}, "raw": (Value::from(raw))});
+        let expected = serde_json::json!({
+            "a": [null, true, 1.5, -2, "s"],
+            "b": {},
+            "raw": {"r": [1, "é"]},
+        });
+
+        assert_eq!(value, expected);
+        assert_eq!(expected, value);
+        assert_eq!(serde_json::Value::from(value.clone()), expected);
+        assert_eq!(Value::from(expected.clone()), expected);
+        let Value::Object(map) = value else {
+            unreachable!()
+        };
+        assert_eq!(
+            serde_json::Map::from(map.clone()),
+            *expected.as_object().unwrap()
+        );
+
+        let mut other = map;
+        other.insert("b", json!([]));
+        assert_ne!(Value::Object(other.clone()), expected);
+        other.remove("b");
+        assert_ne!(Value::Object(other), expected);
+        assert_ne!(json!([1, 2]), serde_json::json!([1]));
+        assert_ne!(json!("1"), serde_json::json!(1));
+        assert_ne!(Value::Object(Map::new()), serde_json::json!(null));
+    }
+
+    #[test]
+    fn raw() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |value: &Value| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        let raw = |text: &'static str| {
+            Value::from(LazyValue::from_bytes(Bytes::from_static(text.as_bytes())).unwrap())
+        };
+
+        let parsed = json!({"a": [1, "é"], "b": null});
+        for value in [
+            raw(r#"{"a": [1, "\u00e9"], "b": null}"#),
+            raw(r#"{"a":[1,"é"],"b":null}"#),
+        ] {
+            assert!(matches!(value, Value::Raw(_)));
+            assert_eq!(value, parsed);
+            assert_eq!(parsed, value);
+            assert_eq!(hash(&value), hash(&parsed));
+            assert_eq!(value, serde_json::Value::from(parsed.clone()));
+        }
+        assert_eq!(raw(r#"{"b": null, "a": [1, "é"]}"#), parsed);
+        assert_eq!(raw("[1, 2]"), raw("[1,2]"));
+        assert_eq!(json!([(raw("true"))]), json!([true]));
+        assert_ne!(raw("1"), json!("1"));
+        assert_ne!(raw("[1]"), raw("[2]"));
+    }
+}
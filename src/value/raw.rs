@@ -0,0 +1,398 @@
+use crate::bytestring;
+use crate::lib::*;
+use crate::value::shared::{self, Shared};
+use crate::value::Value;
+use bytes::Bytes;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, Unexpected, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Error;
+
+/// The struct name serde_json uses to recognize raw values.
+pub(crate) const TOKEN: &str = "$serde_json::private::RawValue";
+
+/// A range of bytes containing a valid JSON value, kept as is.
+///
+/// This is the equivalent of [`serde_json::value::RawValue`] backed by
+/// [`Bytes`]: it is parsed from a buffer without building a tree or copying
+/// the data, and it is serialized verbatim. It can be stored in a [`Value`]
+/// with [`Value::Raw`], to pass through parts of a document that do not need
+/// to be inspected.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, value::RawBytesValue, Value};
+/// let raw = RawBytesValue::from_bytes(Bytes::from_static(b" {\"b\" : [1, 2]} "))?;
+/// assert_eq!(raw.get(), "{\"b\" : [1, 2]}");
+///
+/// let mut value = json!({"a": null});
+/// value["a"] = Value::Raw(raw);
+/// assert_eq!(value.to_bytes(), "{\"a\":{\"b\" : [1, 2]}}");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct RawBytesValue(Bytes, usize);
+
+impl RawBytesValue {
+    /// Validates `bytes` as a JSON value. Surrounding whitespace is removed,
+    /// the rest of the data is referenced without copying.
+    pub fn from_bytes(bytes: Bytes) -> Result<Self, Error> {
+        let mut de = serde_json::Deserializer::from_slice(&bytes);
+        let raw = tri!(RawBytesSeed::new(&bytes).deserialize(&mut de));
+        tri!(de.end());
+        Ok(raw)
+    }
+
+    /// `bytes` must be valid JSON text, without surrounding whitespace.
+    pub(crate) fn from_validated(bytes: Bytes) -> Self {
+        let len = bytes.len();
+        RawBytesValue(bytes, len)
+    }
+
+    /// Returns the text along with the length of the buffer it is a slice of,
+    /// like `ByteString::stored`.
+    pub(crate) fn stored(&self) -> (&Bytes, usize) {
+        (&self.0, self.1)
+    }
+
+    /// Records that the text is a slice of a buffer of `len` bytes, which it
+    /// keeps alive.
+    pub(crate) fn within(self, len: usize) -> Self {
+        RawBytesValue(self.0, len)
+    }
+
+    /// Returns the text in `range`, a slice of the same buffer.
+    pub(crate) fn slice(&self, range: ops::Range<usize>) -> Self {
+        RawBytesValue(self.0.slice(range), self.1)
+    }
+
+    /// Serializes `value` as compact JSON.
+    pub fn from_value(value: &Value) -> Self {
+        RawBytesValue::from_validated(value.to_bytes())
+    }
+
+    /// Returns the JSON text.
+    pub fn get(&self) -> &str {
+        // only created from valid JSON text
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+
+    pub const fn inner(&self) -> &Bytes {
+        &self.0
+    }
+
+    /// Parses the JSON text into a [`Value`]. Strings reference the same
+    /// buffer.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        Value::from_bytes(self.0.clone())
+    }
+
+    /// Returns `text` as a slice of `origin` if it is contained in it, where
+    /// serde_json validated it, or a validated copy otherwise.
+    fn from_str_in(origin: Option<&Bytes>, text: &str) -> Result<Self, Error> {
+        match origin {
+            Some(origin) if bytestring::contains(origin, text) => Ok(RawBytesValue(
+                origin.slice_ref(text.as_bytes()),
+                origin.len(),
+            )),
+            _ => RawBytesValue::from_bytes(Bytes::copy_from_slice(text.as_bytes())),
+        }
+    }
+}
+
+impl PartialEq for RawBytesValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for RawBytesValue {}
+
+impl Hash for RawBytesValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl From<RawBytesValue> for Bytes {
+    fn from(raw: RawBytesValue) -> Self {
+        raw.0
+    }
+}
+
+impl Debug for RawBytesValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_tuple("RawBytesValue")
+            .field(&format_args!("{}", self.get()))
+            .finish()
+    }
+}
+
+impl Display for RawBytesValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+impl Serialize for RawBytesValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = tri!(serializer.serialize_struct(TOKEN, 1));
+        if shared::takes_tokens::<S>() {
+            tri!(s.serialize_field(TOKEN, &Shared(self)));
+        } else {
+            tri!(s.serialize_field(TOKEN, self.get()));
+        }
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RawBytesValue {
+    /// Copies the JSON text, unless it is deserialized with
+    /// [`from_bytes`](crate::from_bytes) or [`RawBytesSeed`], which reference
+    /// the input buffer instead.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, RawVisitor { origin: None })
+    }
+}
+
+/// The [`DeserializeSeed`] implementation that returns [`RawBytesValue`].
+///
+/// When the deserializer borrows from `bytes`, the raw value references it
+/// without copying.
+#[derive(Clone, Copy)]
+pub struct RawBytesSeed<'data> {
+    bytes: &'data Bytes,
+}
+
+impl<'data> RawBytesSeed<'data> {
+    pub const fn new(bytes: &'data Bytes) -> Self {
+        RawBytesSeed { bytes }
+    }
+}
+
+impl<'de, 'data> DeserializeSeed<'de> for RawBytesSeed<'data> {
+    type Value = RawBytesValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(
+            TOKEN,
+            RawVisitor {
+                origin: Some(self.bytes),
+            },
+        )
+    }
+}
+
+struct RawVisitor<'data> {
+    origin: Option<&'data Bytes>,
+}
+
+impl<'de, 'data> Visitor<'de> for RawVisitor<'data> {
+    type Value = RawBytesValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    /// serde_json presents raw values as a map with a single entry.
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if tri!(map.next_key::<RawKey>()).is_none() {
+            return Err(de::Error::invalid_type(Unexpected::Map, &self));
+        }
+        map.next_value_seed(RawText {
+            origin: self.origin,
+        })
+    }
+
+    /// Other deserializers, like the one of [`Value`], hand the raw value
+    /// over or go through a tree that is serialized again.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if let Some(Value::Raw(raw)) = shared::take() {
+            return Ok(raw);
+        }
+        let value = tri!(Value::deserialize(deserializer));
+        Ok(RawBytesValue::from_value(&value))
+    }
+}
+
+struct RawKey;
+
+impl<'de> Deserialize<'de> for RawKey {
+    fn deserialize<D>(deserializer: D) -> Result<RawKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = ();
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("raw value")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<(), E>
+            where
+                E: de::Error,
+            {
+                if s == TOKEN {
+                    Ok(())
+                } else {
+                    Err(de::Error::custom("unexpected raw value"))
+                }
+            }
+        }
+
+        tri!(deserializer.deserialize_identifier(FieldVisitor));
+        Ok(RawKey)
+    }
+}
+
+struct RawText<'data> {
+    origin: Option<&'data Bytes>,
+}
+
+impl<'de, 'data> DeserializeSeed<'de> for RawText<'data> {
+    type Value = RawBytesValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, 'data> Visitor<'de> for RawText<'data> {
+    type Value = RawBytesValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("raw value")
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        RawBytesValue::from_str_in(self.origin, s).map_err(de::Error::custom)
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        RawBytesValue::from_bytes(Bytes::from(s)).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RawBytesSeed, RawBytesValue};
+    use crate::{from_value, json, to_value, Value};
+    use bytes::Bytes;
+    use serde::de::DeserializeSeed;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct Response {
+        data: Value,
+        extensions: RawBytesValue,
+    }
+
+    const RESPONSE: &[u8] = br#"{"data": {"a": 1}, "extensions": {"cost" :[1, 2.50]}}"#;
+
+    #[test]
+    fn zero_copy() {
+        let bytes = Bytes::from_static(br#"  [1, {"a" : "b"}]  "#);
+        let raw = RawBytesValue::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(raw.get(), r#"[1, {"a" : "b"}]"#);
+        assert!(bytes.as_ptr_range().contains(&raw.inner().as_ptr()));
+        assert_eq!(raw.to_value().unwrap(), json!([1, {"a": "b"}]));
+
+        assert!(RawBytesValue::from_bytes(Bytes::from_static(b"[1,")).is_err());
+        assert!(RawBytesValue::from_bytes(Bytes::from_static(b"1 2")).is_err());
+
+        let mut de = serde_json::Deserializer::from_slice(&bytes);
+        let raw = RawBytesSeed::new(&bytes).deserialize(&mut de).unwrap();
+        assert!(bytes.as_ptr_range().contains(&raw.inner().as_ptr()));
+
+        let bytes = Bytes::from_static(RESPONSE);
+        let response: Response = crate::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(response.extensions.get(), r#"{"cost" :[1, 2.50]}"#);
+        assert!(bytes
+            .as_ptr_range()
+            .contains(&response.extensions.inner().as_ptr()));
+    }
+
+    #[test]
+    fn validate_text() {
+        // text given by other deserializers is validated, whether owned or not
+        use serde::de::value::{Error, MapDeserializer};
+        fn owned(text: &str) -> Result<RawBytesValue, Error> {
+            let entry = (super::TOKEN, text.to_owned());
+            RawBytesValue::deserialize(MapDeserializer::new(std::iter::once(entry)))
+        }
+        fn borrowed(text: &str) -> Result<RawBytesValue, Error> {
+            let entry = (super::TOKEN, text);
+            RawBytesValue::deserialize(MapDeserializer::new(std::iter::once(entry)))
+        }
+
+        assert_eq!(owned(" [1] ").unwrap().get(), "[1]");
+        assert_eq!(borrowed(" [1] ").unwrap().get(), "[1]");
+        for invalid in ["[1", "1 2", ""] {
+            assert!(owned(invalid).is_err());
+            assert!(borrowed(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn serialize_verbatim() {
+        let response: Response = serde_json::from_slice(RESPONSE).unwrap();
+        assert_eq!(response.extensions.get(), r#"{"cost" :[1, 2.50]}"#);
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"data":{"a":1},"extensions":{"cost" :[1, 2.50]}}"#
+        );
+
+        let value = to_value(&response).unwrap();
+        assert_eq!(value["extensions"], Value::Raw(response.extensions.clone()));
+        assert_eq!(
+            value.to_bytes(),
+            r#"{"data":{"a":1},"extensions":{"cost" :[1, 2.50]}}"#
+        );
+        assert_eq!(value.to_string(), value.to_bytes());
+    }
+
+    #[test]
+    fn deserialize_from_value() {
+        // a raw value is parsed when deserializing a typed field from it
+        let raw = RawBytesValue::from_bytes(Bytes::from_static(b"{\"a\": [1, 2]}")).unwrap();
+        let value = Value::Raw(raw.clone());
+        let map: std::collections::BTreeMap<String, Vec<u32>> =
+            Deserialize::deserialize(&value).unwrap();
+        assert_eq!(map["a"], [1, 2]);
+        let map: std::collections::BTreeMap<String, Vec<u32>> = from_value(value.clone()).unwrap();
+        assert_eq!(map["a"], [1, 2]);
+        assert_eq!(from_value::<RawBytesValue>(value).unwrap(), raw);
+
+        // a tree is serialized into a raw value
+        let response: Response =
+            from_value(json!({"data": null, "extensions": {"b": [true]}})).unwrap();
+        assert_eq!(response.extensions.get(), r#"{"b":[true]}"#);
+    }
+}
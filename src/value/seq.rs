@@ -0,0 +1,260 @@
+use super::lines::{parse_record, Splitter};
+use crate::lib::*;
+use crate::{ParseOptions, Value};
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::de::Error as _;
+use serde_json::Error;
+
+/// The record separator preceding each text of a sequence.
+const RS: u8 = 0x1E;
+
+/// A reader of JSON text sequences (`application/json-seq`, [RFC 7464]),
+/// returning one [`Value`] per record.
+///
+/// Each JSON text is preceded by an ASCII record separator (0x1E) and usually
+/// followed by a newline. Like [`JsonLines`](crate::JsonLines), the input is
+/// given as a single buffer or chunk by chunk, and strings reference it.
+///
+/// As the RFC requires, an invalid record is reported as an error and the
+/// reader resumes at the next record separator. A top-level number, `true`,
+/// `false` or `null` that is not followed by whitespace might have been
+/// truncated, so it is rejected too. Empty records are skipped.
+///
+/// [RFC 7464]: https://www.rfc-editor.org/rfc/rfc7464
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, JsonSeq};
+/// let input = Bytes::from_static(b"\x1e{\"id\": 1}\n\x1e{\"id\"\x1e12\x1e[2]\n");
+/// let mut records = JsonSeq::from_bytes(input);
+///
+/// assert_eq!(records.next().unwrap()?, json!({"id": 1}));
+/// assert_eq!(
+///     records.next().unwrap().unwrap_err().to_string(),
+///     "EOF while parsing an object at line 2 column 6",
+/// );
+/// assert_eq!(
+///     records.next().unwrap().unwrap_err().to_string(),
+///     "truncated value at line 2 column 9",
+/// );
+/// assert_eq!(records.next().unwrap()?, json!([2]));
+/// assert!(records.next().is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub struct JsonSeq {
+    splitter: Splitter,
+    options: ParseOptions,
+    /// Position of the start of the next record.
+    line: usize,
+    column: usize,
+    /// The first record separator was found.
+    started: bool,
+}
+
+impl JsonSeq {
+    /// Creates a reader waiting for input.
+    pub fn new() -> Self {
+        JsonSeq::with_options(ParseOptions::new())
+    }
+
+    /// Creates a reader parsing each record with `options`. The limits apply
+    /// to each record separately.
+    pub fn with_options(options: ParseOptions) -> Self {
+        JsonSeq {
+            splitter: Splitter::new(RS),
+            options,
+            line: 1,
+            column: 1,
+            started: false,
+        }
+    }
+
+    /// Creates a reader over a complete input.
+    pub fn from_bytes(input: Bytes) -> Self {
+        let mut records = JsonSeq::new();
+        records.feed(input);
+        records.finish();
+        records
+    }
+
+    /// Adds the next chunk of input.
+    pub fn feed(&mut self, chunk: Bytes) {
+        self.splitter.feed(chunk);
+    }
+
+    /// Marks the end of the input, so that the last record is returned.
+    pub fn finish(&mut self) {
+        self.splitter.finish();
+    }
+
+    fn parse(&self, record: Bytes, line: usize, column: usize) -> Result<Value, Error> {
+        let (mut end_line, mut end_column) = (line, column);
+        advance(&mut end_line, &mut end_column, &record);
+        let complete = record.last().is_some_and(u8::is_ascii_whitespace);
+
+        let value = tri!(parse_record(record, &self.options, line, column));
+        match value {
+            Value::Number(_) | Value::Bool(_) | Value::Null if !complete => {
+                Err(Error::custom(format_args!(
+                    "truncated value at line {end_line} column {}",
+                    end_column - 1
+                )))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+impl Default for JsonSeq {
+    fn default() -> Self {
+        JsonSeq::new()
+    }
+}
+
+impl Iterator for JsonSeq {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = self.splitter.next()?;
+            let (line, column) = (self.line, self.column);
+            advance(&mut self.line, &mut self.column, &record);
+            // the record separator
+            self.column += 1;
+
+            let Some(start) = record.iter().position(|b| !b.is_ascii_whitespace()) else {
+                self.started = true;
+                continue;
+            };
+            if !mem::replace(&mut self.started, true) {
+                let (mut line, mut column) = (line, column);
+                advance(&mut line, &mut column, &record[..start]);
+                return Some(Err(Error::custom(format_args!(
+                    "expected record separator at line {line} column {column}"
+                ))));
+            }
+            return Some(self.parse(record, line, column));
+        }
+    }
+}
+
+/// Moves a position past `bytes`.
+fn advance(line: &mut usize, column: &mut usize, bytes: &[u8]) {
+    match bytes.iter().rposition(|&b| b == b'\n') {
+        Some(last) => {
+            *line += bytes.iter().filter(|&&b| b == b'\n').count();
+            *column = bytes.len() - last;
+        }
+        None => *column += bytes.len(),
+    }
+}
+
+/// A writer of JSON text sequences, appending each [`Value`] to a buffer as
+/// a record separator, compact JSON and a newline.
+///
+/// ```
+/// # use serde_json_bytes::{json, JsonSeqWriter};
+/// let mut writer = JsonSeqWriter::new();
+/// writer.write(&json!({"id": 1}));
+/// writer.write(&json!(2));
+///
+/// assert_eq!(writer.split(), "\x1e{\"id\":1}\n\x1e2\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonSeqWriter {
+    buf: BytesMut,
+}
+
+impl JsonSeqWriter {
+    pub fn new() -> Self {
+        JsonSeqWriter::default()
+    }
+
+    /// Appends to an existing buffer.
+    pub fn with_buffer(buf: BytesMut) -> Self {
+        JsonSeqWriter { buf }
+    }
+
+    /// Appends `value` as a record.
+    pub fn write(&mut self, value: &Value) {
+        self.buf.put_u8(RS);
+        value.to_bytes_into(&mut self.buf);
+        self.buf.put_u8(b'\n');
+    }
+
+    /// Takes the records written so far, leaving the writer empty.
+    pub fn split(&mut self) -> Bytes {
+        self.buf.split().freeze()
+    }
+
+    /// Returns the buffer.
+    pub fn into_inner(self) -> BytesMut {
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonSeq, JsonSeqWriter};
+    use crate::{json, Value};
+    use bytes::Bytes;
+
+    fn collect(records: JsonSeq) -> Vec<Result<Value, String>> {
+        records.map(|r| r.map_err(|e| e.to_string())).collect()
+    }
+
+    #[test]
+    fn read() {
+        let input = "\x1e{\"a\": \"b\"}\n\x1e\x1e\x1e \n\x1e[1,\n 2]\n\x1e\"c\"";
+        let bytes = Bytes::from_static(input.as_bytes());
+        let value = JsonSeq::from_bytes(bytes.clone()).next().unwrap().unwrap();
+        let b = value["a"].as_str().unwrap();
+        assert!(bytes.as_ptr_range().contains(&b.as_ptr()));
+
+        for i in 0..=input.len() {
+            let mut records = JsonSeq::new();
+            let mut values = Vec::new();
+            records.feed(bytes.slice(..i));
+            values.extend(&mut records);
+            records.feed(bytes.slice(i..));
+            records.finish();
+            values.extend(&mut records);
+            let values: Vec<_> = values.into_iter().map(Result::unwrap).collect();
+            assert_eq!(
+                values,
+                [json!({"a": "b"}), json!([1, 2]), json!("c")],
+                "split at {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn recovery() {
+        let input = " 1\n\x1etrue\n\x1e[1,\n2 x\n\x1e{\"a\":\x1e-1.5\x1enull\n\x1e3";
+        assert_eq!(
+            collect(JsonSeq::from_bytes(Bytes::from_static(input.as_bytes()))),
+            [
+                Err("expected record separator at line 1 column 2".to_owned()),
+                Ok(json!(true)),
+                Err("expected `,` or `]` at line 4 column 3".to_owned()),
+                Err("EOF while parsing a value at line 5 column 6".to_owned()),
+                Err("truncated value at line 5 column 11".to_owned()),
+                Ok(json!(null)),
+                Err("truncated value at line 6 column 2".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn write() {
+        let mut writer = JsonSeqWriter::new();
+        let values = [json!({"text": "a\u{1e}b"}), json!(1.5), json!(null)];
+        for value in &values {
+            writer.write(value);
+        }
+        let output = writer.split();
+        assert_eq!(output, "\x1e{\"text\":\"a\\u001eb\"}\n\x1e1.5\n\x1enull\n");
+        let read: Vec<_> = JsonSeq::from_bytes(output).map(Result::unwrap).collect();
+        assert_eq!(read, values);
+    }
+}
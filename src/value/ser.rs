@@ -1,17 +1,34 @@
 use crate::map::Map;
-use crate::value::{to_value, Value};
+use crate::value::shared::{self, Shared};
+use crate::value::{to_value, RawBytesValue, Value};
 use crate::{lib::*, ByteString};
 use serde::de::Unexpected;
 use serde::ser::{Impossible, Serialize};
 use serde_json::error::{Error, Result};
 use serde_json::Number;
 
-#[cfg(feature = "arbitrary_precision")]
-use serde::serde_if_integer128;
-
+/// Serializers of other crates get strings with `serialize_str`, and scan
+/// them for characters to escape: serde has no call for text that is known
+/// not to need escaping. The serializer of [`to_value`] takes the value as
+/// is, sharing its strings, and [`Value::to_bytes`] copies the strings parsed
+/// without escape sequences without scanning them.
 impl Serialize for Value {
     #[inline]
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
+    where
+        S: ::serde::Serializer,
+    {
+        if shared::takes_tokens::<S>() {
+            serializer.serialize_newtype_struct(shared::VALUE_TOKEN, &Shared(self))
+        } else {
+            self.serialize_body(serializer)
+        }
+    }
+}
+
+impl Value {
+    /// Serializes the value through the data model.
+    pub(crate) fn serialize_body<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
     where
         S: ::serde::Serializer,
     {
@@ -19,6 +36,7 @@ impl Serialize for Value {
             Value::Null => serializer.serialize_unit(),
             Value::Bool(b) => serializer.serialize_bool(b),
             Value::Number(ref n) => n.serialize(serializer),
+            // serde has no call for text that does not need escaping
             Value::String(ref s) => serializer.serialize_str(s.as_str()),
             Value::Array(ref v) => v.serialize(serializer),
             #[cfg(any(feature = "std", feature = "alloc"))]
@@ -30,6 +48,7 @@ impl Serialize for Value {
                 }
                 map.end()
             }
+            Value::Raw(ref raw) => raw.serialize(serializer),
         }
     }
 }
@@ -93,10 +112,8 @@ impl serde::Serializer for Serializer {
     }
 
     #[cfg(feature = "arbitrary_precision")]
-    serde_if_integer128! {
-        fn serialize_i128(self, value: i128) -> Result<Value> {
-            Ok(Value::Number(value.into()))
-        }
+    fn serialize_i128(self, value: i128) -> Result<Value> {
+        Ok(Value::Number(value.into()))
     }
 
     #[inline]
@@ -120,10 +137,8 @@ impl serde::Serializer for Serializer {
     }
 
     #[cfg(feature = "arbitrary_precision")]
-    serde_if_integer128! {
-        fn serialize_u128(self, value: u128) -> Result<Value> {
-            Ok(Value::Number(value.into()))
-        }
+    fn serialize_u128(self, value: u128) -> Result<Value> {
+        Ok(Value::Number(value.into()))
     }
 
     #[inline]
@@ -174,11 +189,19 @@ impl serde::Serializer for Serializer {
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Value>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Value>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        match name {
+            shared::STRING_TOKEN | shared::VALUE_TOKEN => {
+                match shared::request(|| value.serialize(self)) {
+                    (_, Some(value)) => Ok(value),
+                    (result, None) => result,
+                }
+            }
+            _ => value.serialize(self),
+        }
     }
 
     fn serialize_newtype_variant<T>(
@@ -251,6 +274,7 @@ impl serde::Serializer for Serializer {
         match name {
             #[cfg(feature = "arbitrary_precision")]
             crate::number::TOKEN => Ok(SerializeMap::Number { out_value: None }),
+            crate::value::raw::TOKEN => Ok(SerializeMap::RawValue { out_value: None }),
             _ => self.serialize_map(Some(len)),
         }
     }
@@ -288,10 +312,15 @@ pub struct SerializeTupleVariant {
 pub enum SerializeMap {
     Map {
         map: Map<ByteString, Value>,
-        next_key: Option<String>,
+        next_key: Option<ByteString>,
     },
     #[cfg(feature = "arbitrary_precision")]
-    Number { out_value: Option<Value> },
+    Number {
+        out_value: Option<Value>,
+    },
+    RawValue {
+        out_value: Option<Value>,
+    },
 }
 
 pub struct SerializeStructVariant {
@@ -386,6 +415,7 @@ impl serde::ser::SerializeMap for SerializeMap {
             }
             #[cfg(feature = "arbitrary_precision")]
             SerializeMap::Number { .. } => unreachable!(),
+            SerializeMap::RawValue { .. } => unreachable!(),
         }
     }
 
@@ -407,6 +437,7 @@ impl serde::ser::SerializeMap for SerializeMap {
             }
             #[cfg(feature = "arbitrary_precision")]
             SerializeMap::Number { .. } => unreachable!(),
+            SerializeMap::RawValue { .. } => unreachable!(),
         }
     }
 
@@ -415,11 +446,12 @@ impl serde::ser::SerializeMap for SerializeMap {
             SerializeMap::Map { map, .. } => Ok(Value::Object(map)),
             #[cfg(feature = "arbitrary_precision")]
             SerializeMap::Number { .. } => unreachable!(),
+            SerializeMap::RawValue { .. } => unreachable!(),
         }
     }
 }
 
-struct MapKeySerializer;
+pub(crate) struct MapKeySerializer;
 
 fn key_must_be_a_string() -> Error {
     use serde::de::Error;
@@ -428,16 +460,16 @@ fn key_must_be_a_string() -> Error {
 }
 
 impl serde::Serializer for MapKeySerializer {
-    type Ok = String;
+    type Ok = ByteString;
     type Error = Error;
 
-    type SerializeSeq = Impossible<String, Error>;
-    type SerializeTuple = Impossible<String, Error>;
-    type SerializeTupleStruct = Impossible<String, Error>;
-    type SerializeTupleVariant = Impossible<String, Error>;
-    type SerializeMap = Impossible<String, Error>;
-    type SerializeStruct = Impossible<String, Error>;
-    type SerializeStructVariant = Impossible<String, Error>;
+    type SerializeSeq = Impossible<ByteString, Error>;
+    type SerializeTuple = Impossible<ByteString, Error>;
+    type SerializeTupleStruct = Impossible<ByteString, Error>;
+    type SerializeTupleVariant = Impossible<ByteString, Error>;
+    type SerializeMap = Impossible<ByteString, Error>;
+    type SerializeStruct = Impossible<ByteString, Error>;
+    type SerializeStructVariant = Impossible<ByteString, Error>;
 
     #[inline]
     fn serialize_unit_variant(
@@ -445,85 +477,91 @@ impl serde::Serializer for MapKeySerializer {
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-    ) -> Result<String> {
-        Ok(variant.to_owned())
+    ) -> Result<ByteString> {
+        Ok(ByteString::from_static(variant))
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<ByteString>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        match name {
+            shared::STRING_TOKEN => match shared::request(|| value.serialize(self)) {
+                (_, Some(Value::String(key))) => Ok(key),
+                (result, _) => result,
+            },
+            _ => value.serialize(self),
+        }
     }
 
-    fn serialize_bool(self, _value: bool) -> Result<String> {
+    fn serialize_bool(self, _value: bool) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_i8(self, value: i8) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_i8(self, value: i8) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_i16(self, value: i16) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_i16(self, value: i16) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_i32(self, value: i32) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_i32(self, value: i32) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_i64(self, value: i64) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_i64(self, value: i64) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_u8(self, value: u8) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_u8(self, value: u8) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_u16(self, value: u16) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_u16(self, value: u16) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_u32(self, value: u32) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_u32(self, value: u32) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_u64(self, value: u64) -> Result<String> {
-        Ok(value.to_string())
+    fn serialize_u64(self, value: u64) -> Result<ByteString> {
+        Ok(value.to_string().into())
     }
 
-    fn serialize_f32(self, _value: f32) -> Result<String> {
+    fn serialize_f32(self, _value: f32) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_f64(self, _value: f64) -> Result<String> {
+    fn serialize_f64(self, _value: f64) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
     #[inline]
-    fn serialize_char(self, value: char) -> Result<String> {
+    fn serialize_char(self, value: char) -> Result<ByteString> {
         Ok({
             let mut s = String::new();
             s.push(value);
-            s
+            s.into()
         })
     }
 
     #[inline]
-    fn serialize_str(self, value: &str) -> Result<String> {
-        Ok(value.to_owned())
+    fn serialize_str(self, value: &str) -> Result<ByteString> {
+        Ok(value.into())
     }
 
-    fn serialize_bytes(self, _value: &[u8]) -> Result<String> {
+    fn serialize_bytes(self, _value: &[u8]) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_unit(self) -> Result<String> {
+    fn serialize_unit(self) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
@@ -533,18 +571,18 @@ impl serde::Serializer for MapKeySerializer {
         _variant_index: u32,
         _variant: &'static str,
         _value: &T,
-    ) -> Result<String>
+    ) -> Result<ByteString>
     where
         T: ?Sized + Serialize,
     {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_none(self) -> Result<String> {
+    fn serialize_none(self) -> Result<ByteString> {
         Err(key_must_be_a_string())
     }
 
-    fn serialize_some<T>(self, _value: &T) -> Result<String>
+    fn serialize_some<T>(self, _value: &T) -> Result<ByteString>
     where
         T: ?Sized + Serialize,
     {
@@ -595,11 +633,11 @@ impl serde::Serializer for MapKeySerializer {
         Err(key_must_be_a_string())
     }
 
-    fn collect_str<T>(self, value: &T) -> Result<String>
+    fn collect_str<T>(self, value: &T) -> Result<ByteString>
     where
         T: ?Sized + Display,
     {
-        Ok(value.to_string())
+        Ok(value.to_string().into())
     }
 }
 
@@ -622,6 +660,17 @@ impl serde::ser::SerializeStruct for SerializeMap {
                     Err(invalid_number())
                 }
             }
+            SerializeMap::RawValue { ref mut out_value } => {
+                if key == crate::value::raw::TOKEN {
+                    *out_value = Some(match shared::request(|| to_value(value)) {
+                        (_, Some(raw)) => raw,
+                        (text, None) => tri!(raw_value(tri!(text))),
+                    });
+                    Ok(())
+                } else {
+                    Err(invalid_raw_value())
+                }
+            }
         }
     }
 
@@ -632,6 +681,9 @@ impl serde::ser::SerializeStruct for SerializeMap {
             SerializeMap::Number { out_value, .. } => {
                 Ok(out_value.expect("number value was not emitted"))
             }
+            SerializeMap::RawValue { out_value, .. } => {
+                Ok(out_value.expect("raw value was not emitted"))
+            }
         }
     }
 }
@@ -657,6 +709,19 @@ impl serde::ser::SerializeStructVariant for SerializeStructVariant {
     }
 }
 
+/// Validates the text of a raw value emitted as a string.
+fn raw_value(text: Value) -> Result<Value> {
+    match text {
+        Value::String(s) => Ok(Value::Raw(tri!(RawBytesValue::from_bytes(s.into_bytes())))),
+        _ => Err(invalid_raw_value()),
+    }
+}
+
+fn invalid_raw_value() -> Error {
+    use serde::de::Error;
+    Error::invalid_value(Unexpected::Other("unknown"), &"raw value")
+}
+
 #[cfg(feature = "arbitrary_precision")]
 struct NumberValueEmitter;
 
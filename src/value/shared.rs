@@ -0,0 +1,196 @@
+//! Passing a [`ByteString`] or a [`Value`] as is between the serializers and
+//! deserializers of this crate and the `Serialize` and `Deserialize`
+//! implementations of its types.
+//!
+//! The implementations make the same data model calls for every format:
+//! `serialize_str` and `deserialize_str` for strings, and the usual calls
+//! for values. Only the serializers and deserializers of this crate take the
+//! shortcut, once they recognize the serializer or visitor types of this
+//! crate. `TypeId` needs `'static` types, which serializers and visitors are
+//! not, so the types are recognized by their name. The visitors have paths
+//! that earlier versions of this crate did not use.
+//!
+//! A serializer of this crate is given the value as a newtype struct with a
+//! private name, and a visitor of this crate is handed the value with
+//! `visit_newtype_struct`. serde cannot carry a `Bytes` buffer through its
+//! data model, so the value is left in a slot: the slot is only filled for
+//! the duration of the call, and emptied when it returns or unwinds.
+
+use crate::lib::*;
+use crate::value::{de, ser, RawBytesValue, Value};
+use crate::ByteString;
+use serde::de::{Error, IntoDeserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Name of the newtype struct a `ByteString` is serialized as.
+pub(crate) const STRING_TOKEN: &str = "$serde_json_bytes::private::ByteString";
+
+/// Name of the newtype struct a `Value` is serialized as.
+pub(crate) const VALUE_TOKEN: &str = "$serde_json_bytes::private::Value";
+
+thread_local! {
+    static SLOT: RefCell<Option<Value>> = const { RefCell::new(None) };
+    /// Whether a serializer of this crate is waiting for an [`offer`].
+    static REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn is<T: ?Sized, U: ?Sized>() -> bool {
+    any::type_name::<T>() == any::type_name::<U>()
+}
+
+/// Returns whether `S` is a serializer of this crate, which takes a
+/// `ByteString` or a `Value` serialized as a newtype struct named with one of
+/// the tokens.
+pub(crate) fn takes_tokens<S: ?Sized>() -> bool {
+    is::<S, ser::Serializer>() || is::<S, ser::MapKeySerializer>()
+}
+
+/// Returns whether `V` is a visitor of this crate that `value` can be handed
+/// over to with [`hand_over`].
+pub(crate) fn takes<'de, V: Visitor<'de>>(value: &Value) -> bool {
+    is::<V, de::ValueVisitor>() || is::<V, StringVisitor>() && matches!(value, Value::String(_))
+}
+
+/// Returns whether `V` is the visitor of `ByteString`.
+pub(crate) fn takes_string<'de, V: Visitor<'de>>() -> bool {
+    is::<V, StringVisitor>()
+}
+
+/// Returns whether `V` is the visitor of `Value`.
+pub(crate) fn takes_value<'de, V: Visitor<'de>>() -> bool {
+    is::<V, de::ValueVisitor>()
+}
+
+/// Calls `visit_newtype_struct` on a visitor of this crate, with `value`
+/// ready to be taken with [`take`].
+pub(crate) fn hand_over<'de, V, E>(value: Value, visitor: V) -> Result<V::Value, E>
+where
+    V: Visitor<'de>,
+    E: Error,
+{
+    struct Empty;
+
+    impl Drop for Empty {
+        fn drop(&mut self) {
+            SLOT.with(|slot| slot.borrow_mut().take());
+        }
+    }
+
+    let _empty = Empty;
+    SLOT.with(|slot| *slot.borrow_mut() = Some(value));
+    visitor.visit_newtype_struct(().into_deserializer())
+}
+
+/// Returns the value handed over to the current `visit_newtype_struct` call,
+/// if a deserializer of this crate made it.
+pub(crate) fn take() -> Option<Value> {
+    SLOT.with(|slot| slot.borrow_mut().take())
+}
+
+/// Runs `serialize` on the content of a newtype struct named with one of the
+/// tokens, and returns the value it handed over with [`offer`], if any.
+pub(crate) fn request<R>(serialize: impl FnOnce() -> R) -> (R, Option<Value>) {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            REQUESTED.with(|requested| requested.set(self.0));
+            SLOT.with(|slot| slot.borrow_mut().take());
+        }
+    }
+
+    let _reset = Reset(REQUESTED.with(|requested| requested.replace(true)));
+    let result = serialize();
+    (result, take())
+}
+
+/// Hands `value` over to the serializer of this crate that called
+/// [`request`]. Returns `false` for any other serializer.
+fn offer(value: impl FnOnce() -> Value) -> bool {
+    if !REQUESTED.with(|requested| requested.replace(false)) {
+        return false;
+    }
+    SLOT.with(|slot| *slot.borrow_mut() = Some(value()));
+    true
+}
+
+/// The content of the newtype struct a value is serialized as: handed over
+/// to the serializer of this crate that asked for it, and serialized
+/// normally otherwise.
+pub(crate) struct Shared<'a, T>(pub(crate) &'a T);
+
+impl Serialize for Shared<'_, ByteString> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if offer(|| Value::String(self.0.clone())) {
+            serializer.serialize_unit()
+        } else {
+            serializer.serialize_str(self.0.as_str())
+        }
+    }
+}
+
+impl Serialize for Shared<'_, Value> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if offer(|| self.0.clone()) {
+            serializer.serialize_unit()
+        } else {
+            self.0.serialize_body(serializer)
+        }
+    }
+}
+
+/// The text of a raw value, which serde_json expects as a string.
+impl Serialize for Shared<'_, RawBytesValue> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if offer(|| Value::Raw(self.0.clone())) {
+            serializer.serialize_unit()
+        } else {
+            serializer.serialize_str(self.0.get())
+        }
+    }
+}
+
+/// The visitor of `ByteString`, which takes the string handed over by the
+/// deserializers of this crate.
+pub(crate) struct StringVisitor;
+
+impl<'de> Visitor<'de> for StringVisitor {
+    type Value = ByteString;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match take() {
+            Some(Value::String(string)) => Ok(string),
+            _ => deserializer.deserialize_str(self),
+        }
+    }
+}
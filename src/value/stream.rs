@@ -0,0 +1,748 @@
+use crate::lib::*;
+use crate::parser::{Event, Parser};
+use crate::{ByteString, DuplicateKeys, Map, ParseOptions, Value};
+use bytes::{Buf, Bytes};
+
+impl Value {
+    /// Parses a JSON document with the crate's own parser, configured by
+    /// `options`.
+    ///
+    /// Unlike [`Value::from_bytes`], trailing characters after the document
+    /// are rejected.
+    pub fn from_bytes_with_options(
+        data: Bytes,
+        options: &ParseOptions,
+    ) -> Result<Value, serde_json::Error> {
+        let mut parser = ValueParser::with_options(options.clone());
+        tri!(parser.feed(data));
+        parser.finish()
+    }
+
+    /// Parses a JSON document from a possibly non-contiguous buffer, like a
+    /// [`Chain`](bytes::buf::Chain) of `Bytes`.
+    ///
+    /// Each chunk is taken with [`Buf::copy_to_bytes`], which does not copy
+    /// for `Bytes` or chains of `Bytes`, so strings reference the original
+    /// segments as long as they do not cross a segment boundary.
+    ///
+    /// ```
+    /// # use bytes::{Buf, Bytes};
+    /// # use serde_json_bytes::{json, Value};
+    /// let head = Bytes::from_static(b"{\"a\": [1, ");
+    /// let tail = Bytes::from_static(b"2], \"b\": \"c\"}");
+    /// let value = Value::from_buf(head.chain(tail))?;
+    ///
+    /// assert_eq!(value, json!({"a": [1, 2], "b": "c"}));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn from_buf(mut buf: impl Buf) -> Result<Value, serde_json::Error> {
+        let mut parser = ValueParser::new();
+        while buf.has_remaining() {
+            let len = buf.chunk().len();
+            tri!(parser.feed(buf.copy_to_bytes(len)));
+        }
+        parser.finish()
+    }
+
+    /// Parses a JSON document split in several segments, for example a
+    /// `VecDeque<Bytes>`.
+    ///
+    /// Strings reference the segments as long as they do not cross a
+    /// segment boundary.
+    pub fn from_segments<I>(segments: I) -> Result<Value, serde_json::Error>
+    where
+        I: IntoIterator<Item = Bytes>,
+    {
+        let mut parser = ValueParser::new();
+        for segment in segments {
+            tri!(parser.feed(segment));
+        }
+        parser.finish()
+    }
+}
+
+/// An incremental parser building a [`Value`] from chunks of bytes as they
+/// arrive, for example from a network socket.
+///
+/// Strings contained in a single chunk reference that chunk without copying.
+/// Only strings split across two or more chunks, and strings containing
+/// escape sequences, are copied.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, ValueParser};
+/// let mut parser = ValueParser::new();
+/// parser.feed(Bytes::from_static(b"{\"name\": \"Jo"))?;
+/// parser.feed(Bytes::from_static(b"hn\", \"age\": 4"))?;
+/// parser.feed(Bytes::from_static(b"2}"))?;
+/// let value = parser.finish()?;
+///
+/// assert_eq!(value, json!({"name": "John", "age": 42}));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub struct ValueParser {
+    parser: Parser,
+    builder: Builder,
+    value: Option<Value>,
+}
+
+impl ValueParser {
+    pub fn new() -> Self {
+        ValueParser::with_options(ParseOptions::new())
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        ValueParser {
+            builder: Builder::new(options.duplicate_keys),
+            parser: Parser::new(options),
+            value: None,
+        }
+    }
+
+    /// Reports positions in error messages from `line` and `column`.
+    pub(crate) fn start_at(mut self, line: usize, column: usize) -> Self {
+        self.parser.start_at(line, column);
+        self
+    }
+
+    /// Parses the next chunk of the document.
+    ///
+    /// Syntax errors are reported as soon as they are found. The parser must
+    /// not be used anymore after an error.
+    pub fn feed(&mut self, chunk: Bytes) -> Result<(), serde_json::Error> {
+        tri!(self.parser.feed(chunk));
+        self.drain()
+    }
+
+    /// Returns the parsed value, or an error if the document is incomplete.
+    pub fn finish(mut self) -> Result<Value, serde_json::Error> {
+        self.parser.end();
+        tri!(self.drain());
+        Ok(self
+            .value
+            .expect("the parser reports incomplete documents as errors"))
+    }
+
+    fn drain(&mut self) -> Result<(), serde_json::Error> {
+        while let Some(event) = tri!(self.parser.next()) {
+            match self.builder.push(event) {
+                Ok(Some(value)) => self.value = Some(value),
+                Ok(None) => {}
+                Err(code) => return Err(self.parser.key_error(&code)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for ValueParser {
+    fn default() -> Self {
+        ValueParser::new()
+    }
+}
+
+/// Assembles parser events into a [`Value`].
+pub(crate) struct Builder {
+    stack: Vec<Frame>,
+    duplicate_keys: DuplicateKeys,
+}
+
+enum Frame {
+    Array(Vec<Value>),
+    Object(Map<ByteString, Value>, Option<ByteString>),
+}
+
+impl Builder {
+    pub(crate) fn new(duplicate_keys: DuplicateKeys) -> Self {
+        Builder {
+            stack: Vec::new(),
+            duplicate_keys,
+        }
+    }
+
+    /// Returns the value once the event completing it is pushed.
+    ///
+    /// Fails with an error message if a key is rejected by the duplicate key
+    /// policy.
+    pub(crate) fn push(&mut self, event: Event) -> Result<Option<Value>, String> {
+        let value = match event {
+            Event::StartArray => {
+                self.stack.push(Frame::Array(Vec::new()));
+                return Ok(None);
+            }
+            Event::StartObject => {
+                self.stack.push(Frame::Object(Map::new(), None));
+                return Ok(None);
+            }
+            Event::Key(k) => {
+                if let Some(Frame::Object(m, key)) = self.stack.last_mut() {
+                    if self.duplicate_keys == DuplicateKeys::Reject && m.contains_key(k.as_str()) {
+                        return Err(self.duplicate_key_error(&k));
+                    }
+                    *key = Some(k);
+                }
+                return Ok(None);
+            }
+            Event::EndArray | Event::EndObject => match self.stack.pop() {
+                Some(Frame::Array(v)) => Value::Array(v),
+                Some(Frame::Object(m, _)) => Value::Object(m),
+                None => unreachable!("unbalanced parser events"),
+            },
+            Event::String(s) => Value::String(s),
+            Event::Number(n) => Value::Number(n),
+            Event::Bool(b) => Value::Bool(b),
+            Event::Null => Value::Null,
+        };
+
+        match self.stack.last_mut() {
+            None => Ok(Some(value)),
+            Some(Frame::Array(v)) => {
+                v.push(value);
+                Ok(None)
+            }
+            Some(Frame::Object(m, key)) => {
+                let key = key
+                    .take()
+                    .expect("the parser emits a key before each value");
+                if self.duplicate_keys != DuplicateKeys::KeepFirst || !m.contains_key(key.as_str())
+                {
+                    m.insert(key, value);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    #[cold]
+    fn duplicate_key_error(&self, key: &ByteString) -> String {
+        // JSON Pointer of the innermost object
+        let mut pointer = String::new();
+        for frame in &self.stack[..self.stack.len() - 1] {
+            match frame {
+                Frame::Array(v) => push_token(&mut pointer, &v.len().to_string()),
+                Frame::Object(_, key) => {
+                    push_token(&mut pointer, key.as_ref().map_or("", ByteString::as_str))
+                }
+            }
+        }
+        duplicate_key_error(key, &pointer)
+    }
+}
+
+/// Appends a reference token to a JSON Pointer.
+pub(crate) fn push_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+/// Error message for a key rejected by [`DuplicateKeys::Reject`], in the
+/// object at `pointer`.
+#[cold]
+pub(crate) fn duplicate_key_error(key: &str, pointer: &str) -> String {
+    if pointer.is_empty() {
+        format!("duplicate key {key:?} in the root object")
+    } else {
+        format!("duplicate key {key:?} in the object at {pointer}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueParser;
+    use crate::value::BytesSeed;
+    use crate::{json, DuplicateKeys, KeyInterner, LimitExceeded, ParseOptions, Value};
+    use bytes::{Buf, Bytes};
+    use serde::de::DeserializeSeed;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    const DOCUMENT: &str = r#"{
+        "string": "hello",
+        "escaped": "a\"b\\c\n\u00e9\ud83e\udd80",
+        "unicode": "日本 🦀",
+        "numbers": [0, -1, 2.5, 1e3, -0.0, 18446744073709551615, 123456789012],
+        "literals": [true, false, null],
+        "nested": {"a": [[], {}], "b": {"c": [1, [2, [3]]]}},
+        "dup": 1,
+        "dup": 2
+    }"#;
+
+    fn parse_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Result<Value, String> {
+        let mut parser = ValueParser::new();
+        for chunk in chunks {
+            parser
+                .feed(Bytes::copy_from_slice(chunk))
+                .map_err(|e| e.to_string())?;
+        }
+        parser.finish().map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn every_split() {
+        let expected = Value::from_bytes(Bytes::from_static(DOCUMENT.as_bytes())).unwrap();
+        let bytes = DOCUMENT.as_bytes();
+        for i in 0..=bytes.len() {
+            let value = parse_chunks([&bytes[..i], &bytes[i..]]).unwrap();
+            assert_eq!(value, expected, "split at {i}");
+        }
+        let value = parse_chunks(bytes.chunks(1)).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn scalars() {
+        for input in ["1", "-12.5e-3", "\"s\"", "true", "null", " 0 "] {
+            let expected = Value::from_bytes(Bytes::from_static(input.as_bytes())).unwrap();
+            assert_eq!(parse_chunks(input.as_bytes().chunks(1)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn zero_copy() {
+        let chunk = Bytes::from_static(b"{\"key\": \"value\", \"other\": \"spl");
+        let mut parser = ValueParser::new();
+        parser.feed(chunk.clone()).unwrap();
+        parser.feed(Bytes::from_static(b"it\"}")).unwrap();
+        let value = parser.finish().unwrap();
+
+        let in_chunk = |s: &str| chunk.as_ptr_range().contains(&s.as_ptr());
+        let (key, string) = match &value {
+            Value::Object(map) => map.iter().next().unwrap(),
+            _ => panic!("expected an object"),
+        };
+        assert!(in_chunk(key.as_str()));
+        assert!(in_chunk(string.as_str().unwrap()));
+        assert!(!in_chunk(value["other"].as_str().unwrap()));
+        assert_eq!(value["other"], "split");
+    }
+
+    #[test]
+    fn segmented_buffers() {
+        let bytes = Bytes::from_static(DOCUMENT.as_bytes());
+        let expected = Value::from_bytes(bytes.clone()).unwrap();
+        let (head, tail) = (bytes.slice(..60), bytes.slice(60..));
+
+        let value = Value::from_buf(head.clone().chain(tail.clone())).unwrap();
+        assert_eq!(value, expected);
+        // "string": "hello" is in the first segment
+        let hello = value["string"].as_str().unwrap();
+        assert!(head.as_ptr_range().contains(&hello.as_ptr()));
+
+        let segments: VecDeque<Bytes> = bytes.chunks(7).map(Bytes::copy_from_slice).collect();
+        assert_eq!(Value::from_segments(segments).unwrap(), expected);
+        assert_eq!(Value::from_buf(DOCUMENT.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn deferred_unescape() {
+        let bytes = Bytes::from_static(DOCUMENT.as_bytes());
+        let expected = Value::from_bytes(bytes.clone()).unwrap();
+        let options = ParseOptions::new().defer_unescape(true);
+        let value = Value::from_bytes_with_options(bytes.clone(), &options).unwrap();
+        assert_eq!(value, expected);
+
+        let escaped = match &value["escaped"] {
+            Value::String(s) => s.raw_escaped().unwrap(),
+            _ => panic!("expected a string"),
+        };
+        assert!(bytes.as_ptr_range().contains(&escaped.as_ptr()));
+        assert_eq!(escaped, r#"a\"b\\c\n\u00e9\ud83e\udd80"#);
+        assert_eq!(value["escaped"], "a\"b\\c\n\u{e9}\u{1f980}");
+
+        let invalid = Bytes::from_static(br#"{"k\u0065y": ["\u00e9", "\ud800"]}"#);
+        assert_eq!(
+            Value::from_bytes_with_options(invalid.clone(), &options)
+                .unwrap_err()
+                .to_string(),
+            Value::from_bytes(invalid).unwrap_err().to_string()
+        );
+        let value = Value::from_bytes_with_options(
+            Bytes::from_static(br#"{"k\u0065y": ["\u00e9"]}"#),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(value.to_bytes(), r#"{"key":["\u00e9"]}"#);
+    }
+
+    #[test]
+    fn arena() {
+        let bytes = Bytes::from_static(DOCUMENT.as_bytes());
+        let expected = Value::from_bytes(bytes.clone()).unwrap();
+        let options = ParseOptions::new().arena(true);
+        assert_eq!(
+            Value::from_bytes_with_options(bytes, &options).unwrap(),
+            expected
+        );
+
+        // long enough not to be stored inline
+        let long = "x".repeat(40);
+        let mut parser = ValueParser::with_options(options);
+        parser
+            .feed(Bytes::from(format!(r#"["a\n{long}", "b\t{long}", "spl"#)))
+            .unwrap();
+        parser
+            .feed(Bytes::from(format!(r#"it{long}", "c"]"#)))
+            .unwrap();
+        let value = parser.finish().unwrap();
+        assert_eq!(
+            value,
+            json!([
+                format!("a\n{long}"),
+                format!("b\t{long}"),
+                format!("split{long}"),
+                "c"
+            ])
+        );
+
+        // the copied strings are consecutive slices of one buffer
+        let strings: Vec<&str> = (0..3).map(|i| value[i].as_str().unwrap()).collect();
+        for pair in strings.windows(2) {
+            assert_eq!(
+                pair[0].as_ptr().wrapping_add(pair[0].len()),
+                pair[1].as_ptr()
+            );
+        }
+    }
+
+    #[test]
+    fn key_interner() {
+        let interner = Arc::new(KeyInterner::new());
+        let options = ParseOptions::new().key_interner(interner.clone());
+        let bytes = Bytes::from_static(DOCUMENT.as_bytes());
+        assert_eq!(
+            Value::from_bytes_with_options(bytes.clone(), &options).unwrap(),
+            Value::from_bytes(bytes).unwrap()
+        );
+
+        // escaped, split and plain occurrences of the same long key
+        let long = "k".repeat(40);
+        let mut parser = ValueParser::with_options(options);
+        parser
+            .feed(Bytes::from(format!(
+                r#"[{{"\u006b{}": 1}}, {{"{long}": 2}}, {{"{}"#,
+                &long[1..],
+                &long[..20]
+            )))
+            .unwrap();
+        parser
+            .feed(Bytes::from(format!(r#"{}": 3}}]"#, &long[20..])))
+            .unwrap();
+        let value = parser.finish().unwrap();
+        let keys: Vec<&str> = (0..3)
+            .map(|i| {
+                value[i]
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .next()
+                    .unwrap()
+                    .as_str()
+            })
+            .collect();
+        assert!(keys.iter().all(|key| *key == long));
+        assert!(keys.iter().all(|key| key.as_ptr() == keys[0].as_ptr()));
+    }
+
+    #[test]
+    fn limits() {
+        let parse = |input: &'static str, options: ParseOptions| {
+            let bytes = Bytes::from_static(input.as_bytes());
+            Value::from_bytes_with_options(bytes, &options).map_err(|e| e.to_string())
+        };
+
+        assert!(parse("[[1]]", ParseOptions::new().max_depth(2)).is_ok());
+        assert_eq!(
+            parse("[[[1]]]", ParseOptions::new().max_depth(2)).unwrap_err(),
+            "maximum depth of 2 exceeded at line 1 column 3"
+        );
+
+        let options = ParseOptions::new().max_string_length(3);
+        assert!(parse(r#"{"abc": "d\n"}"#, options.clone()).is_ok());
+        assert_eq!(
+            parse(r#"{"abcd": 1}"#, options.clone()).unwrap_err(),
+            "maximum string length of 3 bytes exceeded at line 1 column 6"
+        );
+        let mut parser = ValueParser::with_options(options);
+        parser.feed(Bytes::from_static(b"[\"ab")).unwrap();
+        assert_eq!(
+            parser
+                .feed(Bytes::from_static(b"cd\"]"))
+                .unwrap_err()
+                .to_string(),
+            "maximum string length of 3 bytes exceeded at line 1 column 6"
+        );
+
+        assert!(parse(
+            r#"{"a": 1, "b": [2]}"#,
+            ParseOptions::new().max_object_keys(2)
+        )
+        .is_ok());
+        assert_eq!(
+            parse(
+                r#"{"a": 1, "b": 2, "c": 3}"#,
+                ParseOptions::new().max_object_keys(2)
+            )
+            .unwrap_err(),
+            "maximum number of object keys of 2 exceeded at line 1 column 23"
+        );
+
+        assert!(parse("[1, [2, 3]]", ParseOptions::new().max_array_length(2)).is_ok());
+        assert_eq!(
+            parse("[1, 2, 3]", ParseOptions::new().max_array_length(2)).unwrap_err(),
+            "maximum array length of 2 exceeded at line 1 column 8"
+        );
+
+        // the array, the object, "a" and "b"
+        assert!(parse(r#"[{"k": "a"}, "b"]"#, ParseOptions::new().max_nodes(4)).is_ok());
+        assert_eq!(
+            parse(
+                r#"[{"k": "a"}, "b", null]"#,
+                ParseOptions::new().max_nodes(4)
+            )
+            .unwrap_err(),
+            "maximum number of values of 4 exceeded at line 1 column 19"
+        );
+
+        let mut parser = ValueParser::with_options(ParseOptions::new().max_size(6));
+        parser.feed(Bytes::from_static(b"[1, ")).unwrap();
+        let err = parser.feed(Bytes::from_static(b"\n2, 3]")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "maximum input size of 6 bytes exceeded at line 2 column 2"
+        );
+        assert_eq!(
+            LimitExceeded::from_error(&err),
+            Some(LimitExceeded::Size(6))
+        );
+        assert_eq!(err.line(), 2);
+
+        let err = Value::from_bytes(Bytes::from_static(b"[1,")).unwrap_err();
+        assert_eq!(LimitExceeded::from_error(&err), None);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let parse = |input: &'static str, policy| {
+            let options = ParseOptions::new().duplicate_keys(policy);
+            Value::from_bytes_with_options(Bytes::from_static(input.as_bytes()), &options)
+                .map_err(|e| e.to_string())
+        };
+        let input = r#"{"a": 1, "b": {"c": 2}, "a": [3], "b": {"d": 4}}"#;
+
+        assert_eq!(
+            parse(input, DuplicateKeys::KeepFirst).unwrap(),
+            json!({"a": 1, "b": {"c": 2}})
+        );
+        assert_eq!(
+            parse(input, DuplicateKeys::KeepLast).unwrap(),
+            json!({"a": [3], "b": {"d": 4}})
+        );
+        assert_eq!(
+            parse(input, DuplicateKeys::Reject).unwrap_err(),
+            "duplicate key \"a\" in the root object at line 1 column 27"
+        );
+        assert_eq!(
+            parse(
+                r#"{"x": [0, {"a/b": {"k": 1, "k": 2}}]}"#,
+                DuplicateKeys::Reject
+            )
+            .unwrap_err(),
+            "duplicate key \"k\" in the object at /x/1/a~1b at line 1 column 30"
+        );
+        assert!(parse(r#"[{"a": 1}, {"a": 2}]"#, DuplicateKeys::Reject).is_ok());
+    }
+
+    #[test]
+    fn duplicate_key_position() {
+        let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Reject);
+        for input in [
+            "{\n  \"a\": 1,\n  \"b\": 2,\n  \"a\": 3\n}",
+            "{\n  \"a\": 1,\n  \"\\u0061\": 3\n}",
+        ] {
+            let bytes = Bytes::from_static(input.as_bytes());
+            let expected = if input.contains("\\u") {
+                (3, 10)
+            } else {
+                (4, 5)
+            };
+
+            let err = Value::from_bytes_with_options(bytes.clone(), &options).unwrap_err();
+            assert_eq!((err.line(), err.column()), expected, "{input}");
+
+            let mut parser = ValueParser::with_options(options.clone());
+            let err = bytes
+                .chunks(1)
+                .try_for_each(|chunk| parser.feed(Bytes::copy_from_slice(chunk)))
+                .unwrap_err();
+            assert_eq!((err.line(), err.column()), expected, "{input}");
+
+            let mut de = serde_json::Deserializer::from_slice(&bytes);
+            let err = BytesSeed::with_options(&bytes, &options)
+                .deserialize(&mut de)
+                .unwrap_err();
+            assert_eq!((err.line(), err.column()), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn lenient() {
+        const CONFIG: &[u8] = b"\xEF\xBB\xBF// service configuration
+        {
+            name: 'router', /* inline */ \"quoted\": \"value\",
+            $ref_2: 'it\\'s \"here\"',
+            limits: [0x10, -0X1f, 0xFFFFFFFFFFFFFFFF, 1.5e3,],
+            nested: {a: null,},
+        } // trailing comment";
+        // arbitrary precision numbers keep the exponent
+        let exponent: serde_json::Number = "1.5e3".parse().unwrap();
+        let expected = json!({
+            "name": "router",
+            "quoted": "value",
+            "$ref_2": "it's \"here\"",
+            "limits": [16, -31, u64::MAX, exponent],
+            "nested": {"a": null},
+        });
+
+        let options = ParseOptions::new().lenient(true);
+        let parse = |chunks: &mut dyn Iterator<Item = &[u8]>| {
+            let mut parser = ValueParser::with_options(options.clone());
+            for chunk in chunks {
+                parser.feed(Bytes::copy_from_slice(chunk))?;
+            }
+            parser.finish()
+        };
+        for i in 0..=CONFIG.len() {
+            let value = parse(&mut [&CONFIG[..i], &CONFIG[i..]].into_iter()).unwrap();
+            assert_eq!(value, expected, "split at {i}");
+        }
+        assert_eq!(parse(&mut CONFIG.chunks(1)).unwrap(), expected);
+
+        // unquoted keys and single quoted strings reference the input
+        let bytes = Bytes::from_static(CONFIG);
+        let value = Value::from_bytes_with_options(bytes.clone(), &options).unwrap();
+        let (key, name) = match &value {
+            Value::Object(map) => map.iter().find(|(k, _)| k.as_str() == "name").unwrap(),
+            _ => panic!("expected an object"),
+        };
+        assert!(bytes.as_ptr_range().contains(&key.as_str().as_ptr()));
+        assert!(bytes
+            .as_ptr_range()
+            .contains(&name.as_str().unwrap().as_ptr()));
+
+        let error = |input: &'static str, options: &ParseOptions| {
+            Value::from_bytes_with_options(Bytes::from_static(input.as_bytes()), options)
+                .unwrap_err()
+                .to_string()
+        };
+        let strict = ParseOptions::new();
+        assert_eq!(
+            error("[1] // c", &strict),
+            "trailing characters at line 1 column 5"
+        );
+        assert_eq!(
+            error("{a: 1}", &strict),
+            "key must be a string at line 1 column 2"
+        );
+        assert_eq!(error("['a']", &strict), "expected value at line 1 column 2");
+        assert_eq!(
+            error("[0x1]", &strict),
+            "expected `,` or `]` at line 1 column 3"
+        );
+        assert_eq!(
+            error("\u{feff}1", &strict),
+            "expected value at line 1 column 1"
+        );
+        assert_eq!(
+            error(r#"["\'"]"#, &options),
+            "invalid escape at line 1 column 4"
+        );
+        assert_eq!(
+            error("[1] /* c", &options),
+            "EOF while parsing a comment at line 1 column 8"
+        );
+        assert_eq!(
+            error("[1] / c", &options),
+            "expected `/` or `*` after `/` at line 1 column 6"
+        );
+        assert_eq!(error("[0x]", &options), "invalid number at line 1 column 4");
+        assert_eq!(
+            error("[0xg]", &options),
+            "invalid number at line 1 column 4"
+        );
+        assert_eq!(
+            error("0x10000000000000000", &options),
+            "number out of range at line 1 column 19"
+        );
+        assert_eq!(
+            error("-0x8000000000000001", &options),
+            "number out of range at line 1 column 19"
+        );
+        assert_eq!(
+            error("{1a: 2}", &options),
+            "key must be a string at line 1 column 2"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            "",
+            "[1, 2",
+            "{\"a\": 1",
+            "{\"a\":",
+            "\"abc",
+            "[1,]",
+            "{\"a\": 1,}",
+            "[1 2]",
+            "{1: 2}",
+            "{\"a\" 2}",
+            "[\n  tru]",
+            "01",
+            "[-1.]",
+            "[1-2]",
+            "[1.2.3]",
+            "[1e5e5]",
+            "[3-]",
+            "[1--1]",
+            "1-",
+            "--1",
+            "1e+-2",
+            "-",
+            "1e",
+            "[-01]",
+            "1e400",
+            "\"\\x\"",
+            "\"\\ud800\"",
+            "\"\\u00zz\"",
+            "[\"a\u{1}\"]",
+        ];
+        for input in cases {
+            let bytes = input.as_bytes();
+            if cfg!(feature = "arbitrary_precision") && input == "1e400" {
+                // out of range numbers are kept as text
+                assert!(parse_chunks([bytes]).is_ok());
+                continue;
+            }
+            // serde_json itself, which also rejects trailing characters
+            let expected = serde_json::from_slice::<serde_json::Value>(bytes)
+                .unwrap_err()
+                .to_string();
+            assert_eq!(parse_chunks([bytes]).unwrap_err(), expected, "{input}");
+            assert_eq!(
+                parse_chunks(bytes.chunks(1)).unwrap_err(),
+                expected,
+                "{input}"
+            );
+        }
+
+        assert_eq!(
+            parse_chunks([&b"1 2"[..]]).unwrap_err(),
+            "trailing characters at line 1 column 3"
+        );
+        let nested = "[".repeat(129);
+        assert_eq!(
+            parse_chunks([nested.as_bytes()]).unwrap_err(),
+            "recursion limit exceeded at line 1 column 129"
+        );
+    }
+}
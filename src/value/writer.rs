@@ -0,0 +1,270 @@
+use super::encode;
+use crate::lib::*;
+use crate::{ByteString, Value};
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::ser::Error as _;
+use serde_json::Error;
+
+/// Writes a JSON document piece by piece into a [`BytesMut`], without
+/// building a [`Value`] first.
+///
+/// Arrays and objects are opened with [`begin_array`](JsonWriter::begin_array)
+/// and [`begin_object`](JsonWriter::begin_object), and closed with
+/// [`end`](JsonWriter::end). Existing values and pre-serialized JSON can be
+/// spliced in with [`value`](JsonWriter::value) and [`raw`](JsonWriter::raw).
+/// The output is compact, like [`Value::to_bytes`].
+///
+/// The writer checks that calls are correctly nested, for example that each
+/// entry of an object has a key. A call that would produce invalid JSON fails
+/// without writing anything, and the writer can still be used.
+///
+/// ```
+/// # use bytes::Bytes;
+/// # use serde_json_bytes::{json, ByteString, JsonWriter};
+/// let fragment = json!({"id": 1, "name": "a"});
+/// let mut writer = JsonWriter::new();
+/// writer.begin_object()?;
+/// writer.key_str("data")?;
+/// writer.begin_object()?;
+/// writer.key(&ByteString::from("node"))?;
+/// writer.value(&fragment)?;
+/// writer.key_str("cached")?;
+/// writer.raw(&Bytes::from_static(b"[1,2]"))?;
+/// writer.end()?;
+/// writer.end()?;
+///
+/// assert_eq!(
+///     writer.finish()?,
+///     r#"{"data":{"node":{"id":1,"name":"a"},"cached":[1,2]}}"#,
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct JsonWriter {
+    buf: BytesMut,
+    /// Open arrays and objects.
+    stack: Vec<Frame>,
+    /// A key was written, and its value was not.
+    after_key: bool,
+    /// The top level value was started.
+    started: bool,
+}
+
+#[derive(Debug)]
+struct Frame {
+    object: bool,
+    empty: bool,
+}
+
+impl JsonWriter {
+    pub fn new() -> Self {
+        JsonWriter::default()
+    }
+
+    /// Appends the document to an existing buffer.
+    pub fn with_buffer(buf: BytesMut) -> Self {
+        JsonWriter {
+            buf,
+            ..JsonWriter::default()
+        }
+    }
+
+    /// Opens an object. Its entries are written with a key followed by a
+    /// value.
+    pub fn begin_object(&mut self) -> Result<(), Error> {
+        tri!(self.before_value());
+        self.buf.put_u8(b'{');
+        self.stack.push(Frame {
+            object: true,
+            empty: true,
+        });
+        Ok(())
+    }
+
+    /// Opens an array.
+    pub fn begin_array(&mut self) -> Result<(), Error> {
+        tri!(self.before_value());
+        self.buf.put_u8(b'[');
+        self.stack.push(Frame {
+            object: false,
+            empty: true,
+        });
+        Ok(())
+    }
+
+    /// Closes the innermost array or object.
+    pub fn end(&mut self) -> Result<(), Error> {
+        match self.stack.last() {
+            None => Err(Error::custom("no array or object to end")),
+            Some(_) if self.after_key => Err(Error::custom("expected a value after the key")),
+            Some(frame) => {
+                self.buf.put_u8(if frame.object { b'}' } else { b']' });
+                self.stack.pop();
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes the key of the next entry of an object.
+    pub fn key(&mut self, key: &ByteString) -> Result<(), Error> {
+        tri!(self.before_key());
+        encode::write_string(key, &mut self.buf);
+        self.buf.put_u8(b':');
+        Ok(())
+    }
+
+    /// Writes the key of the next entry of an object, escaping it.
+    pub fn key_str(&mut self, key: &str) -> Result<(), Error> {
+        tri!(self.before_key());
+        encode::write_str(key, &mut self.buf);
+        self.buf.put_u8(b':');
+        Ok(())
+    }
+
+    /// Writes a complete value.
+    pub fn value(&mut self, value: &Value) -> Result<(), Error> {
+        tri!(self.before_value());
+        value.to_bytes_into(&mut self.buf);
+        Ok(())
+    }
+
+    /// Writes a string value, escaping it.
+    pub fn string(&mut self, value: &str) -> Result<(), Error> {
+        tri!(self.before_value());
+        encode::write_str(value, &mut self.buf);
+        Ok(())
+    }
+
+    /// Copies a value that is already serialized as JSON.
+    ///
+    /// `json` is written as is, it must be a single valid JSON value.
+    pub fn raw(&mut self, json: &[u8]) -> Result<(), Error> {
+        tri!(self.before_value());
+        self.buf.put_slice(json);
+        Ok(())
+    }
+
+    /// Returns the document, or an error if it is incomplete.
+    pub fn finish(self) -> Result<Bytes, Error> {
+        match self.stack.last() {
+            _ if !self.started => Err(Error::custom("no value was written")),
+            Some(frame) if frame.object => Err(Error::custom("unclosed object")),
+            Some(_) => Err(Error::custom("unclosed array")),
+            None => Ok(self.buf.freeze()),
+        }
+    }
+
+    fn before_key(&mut self) -> Result<(), Error> {
+        match self.stack.last_mut() {
+            Some(_) if self.after_key => Err(Error::custom("expected a value after the key")),
+            Some(frame) if frame.object => {
+                if !mem::replace(&mut frame.empty, false) {
+                    self.buf.put_u8(b',');
+                }
+                self.after_key = true;
+                Ok(())
+            }
+            _ => Err(Error::custom("keys can only be written in objects")),
+        }
+    }
+
+    fn before_value(&mut self) -> Result<(), Error> {
+        match self.stack.last_mut() {
+            None if self.started => Err(Error::custom("the top level value is already written")),
+            None => {
+                self.started = true;
+                Ok(())
+            }
+            Some(frame) if frame.object => {
+                if !mem::replace(&mut self.after_key, false) {
+                    return Err(Error::custom("expected a key before the value"));
+                }
+                Ok(())
+            }
+            Some(frame) => {
+                if !mem::replace(&mut frame.empty, false) {
+                    self.buf.put_u8(b',');
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonWriter;
+    use crate::{json, ByteString, ParseOptions, Value};
+    use bytes::Bytes;
+
+    #[test]
+    fn write() {
+        let escaped = Value::from_bytes_with_options(
+            Bytes::from_static(br#""aA\n""#),
+            &ParseOptions::new().defer_unescape(true),
+        )
+        .unwrap();
+        let mut writer = JsonWriter::new();
+        writer.begin_array().unwrap();
+        writer.begin_object().unwrap();
+        writer.end().unwrap();
+        writer.begin_object().unwrap();
+        writer.key_str("k\"1").unwrap();
+        writer.string("v\t").unwrap();
+        writer.key(&ByteString::from("k2")).unwrap();
+        writer.begin_array().unwrap();
+        writer.end().unwrap();
+        writer.key_str("k3").unwrap();
+        writer.value(&escaped).unwrap();
+        writer.end().unwrap();
+        writer.raw(b"{\"raw\": true}").unwrap();
+        writer.value(&json!([null, 1.5])).unwrap();
+        writer.end().unwrap();
+
+        let output = writer.finish().unwrap();
+        assert_eq!(
+            output,
+            r#"[{},{"k\"1":"v\t","k2":[],"k3":"aA\n"},{"raw": true},[null,1.5]]"#
+        );
+        assert_eq!(
+            Value::from_bytes(output).unwrap(),
+            json!([{}, {"k\"1": "v\t", "k2": [], "k3": "aA\n"}, {"raw": true}, [null, 1.5]])
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        fn err<T: std::fmt::Debug>(result: Result<T, serde_json::Error>) -> String {
+            result.unwrap_err().to_string()
+        }
+
+        let mut writer = JsonWriter::new();
+        assert_eq!(
+            err(writer.key_str("a")),
+            "keys can only be written in objects"
+        );
+        assert_eq!(err(writer.end()), "no array or object to end");
+        writer.begin_object().unwrap();
+        assert_eq!(err(writer.string("a")), "expected a key before the value");
+        writer.key_str("a").unwrap();
+        assert_eq!(err(writer.key_str("b")), "expected a value after the key");
+        assert_eq!(err(writer.end()), "expected a value after the key");
+        writer.begin_array().unwrap();
+        assert_eq!(
+            err(writer.key_str("b")),
+            "keys can only be written in objects"
+        );
+        writer.end().unwrap();
+        writer.end().unwrap();
+        assert_eq!(
+            err(writer.string("a")),
+            "the top level value is already written"
+        );
+        assert_eq!(writer.finish().unwrap(), r#"{"a":[]}"#);
+
+        assert_eq!(err(JsonWriter::new().finish()), "no value was written");
+        let mut writer = JsonWriter::new();
+        writer.begin_array().unwrap();
+        assert_eq!(err(writer.finish()), "unclosed array");
+    }
+}